@@ -0,0 +1,81 @@
+//! Compare serializing and deserializing many small cells with and without `#[xmlserde(inline)]`.
+//!
+//! ```sh
+//! cargo bench --bench cells
+//! ```
+use std::time::Instant;
+use xmlserde::{xml_deserialize_from_str, xml_serialize};
+use xmlserde_derives::{XmlDeserialize, XmlSerialize};
+
+#[derive(XmlSerialize, XmlDeserialize)]
+struct Cell {
+    #[xmlserde(name = b"r", ty = "attr")]
+    r: u32,
+    #[xmlserde(name = b"s", ty = "attr")]
+    style: Option<u32>,
+}
+
+#[derive(XmlSerialize, XmlDeserialize)]
+#[xmlserde(root = b"row")]
+struct Row {
+    #[xmlserde(name = b"c", ty = "child")]
+    cells: Vec<Cell>,
+}
+
+#[derive(XmlSerialize, XmlDeserialize)]
+#[xmlserde(inline)]
+struct InlineCell {
+    #[xmlserde(name = b"r", ty = "attr")]
+    r: u32,
+    #[xmlserde(name = b"s", ty = "attr")]
+    style: Option<u32>,
+}
+
+#[derive(XmlSerialize, XmlDeserialize)]
+#[xmlserde(root = b"row")]
+struct InlineRow {
+    #[xmlserde(name = b"c", ty = "child")]
+    cells: Vec<InlineCell>,
+}
+
+const CELLS: u32 = 200_000;
+
+fn main() {
+    let row = Row {
+        cells: (0..CELLS)
+            .map(|r| Cell {
+                r,
+                style: Some(r % 7),
+            })
+            .collect(),
+    };
+    let now = Instant::now();
+    let xml = xml_serialize(row);
+    println!("serialize:          {:?} ({})", now.elapsed(), xml.len());
+    let now = Instant::now();
+    let row = xml_deserialize_from_str::<Row>(&xml).unwrap();
+    println!(
+        "deserialize:        {:?} ({})",
+        now.elapsed(),
+        row.cells.len()
+    );
+
+    let row = InlineRow {
+        cells: (0..CELLS)
+            .map(|r| InlineCell {
+                r,
+                style: Some(r % 7),
+            })
+            .collect(),
+    };
+    let now = Instant::now();
+    let xml = xml_serialize(row);
+    println!("inline serialize:   {:?} ({})", now.elapsed(), xml.len());
+    let now = Instant::now();
+    let row = xml_deserialize_from_str::<InlineRow>(&xml).unwrap();
+    println!(
+        "inline deserialize: {:?} ({})",
+        now.elapsed(),
+        row.cells.len()
+    );
+}
@@ -0,0 +1,73 @@
+//! Count the allocations and the time of deserializing a deeply nested document.
+//!
+//! ```sh
+//! cargo bench --bench nested
+//! ```
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use xmlserde::xml_deserialize_from_str;
+use xmlserde_derives::XmlDeserialize;
+
+struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+#[derive(XmlDeserialize)]
+#[xmlserde(root = b"node")]
+struct Node {
+    #[xmlserde(name = b"id", ty = "attr")]
+    id: u32,
+    #[xmlserde(name = b"node", ty = "child")]
+    children: Vec<Node>,
+}
+
+const DEPTH: u32 = 50;
+const WIDTH: u32 = 3;
+const N: usize = 200;
+
+fn document() -> String {
+    let mut xml = String::new();
+    (0..DEPTH).for_each(|i| {
+        (0..WIDTH).for_each(|j| xml.push_str(&format!(r#"<node id="{}"/>"#, j)));
+        xml.push_str(&format!(r#"<node id="{}">"#, i));
+    });
+    (0..DEPTH).for_each(|_| xml.push_str("</node>"));
+    xml
+}
+
+fn sum_ids(node: &Node) -> u64 {
+    node.id as u64 + node.children.iter().map(sum_ids).sum::<u64>()
+}
+
+fn main() {
+    let xml = format!("<node id=\"0\">{}</node>", document());
+    let before = ALLOCS.load(Ordering::Relaxed);
+    let now = Instant::now();
+    let mut ids = 0;
+    for _ in 0..N {
+        let node = xml_deserialize_from_str::<Node>(&xml).unwrap();
+        ids += sum_ids(&node);
+    }
+    let allocs = ALLOCS.load(Ordering::Relaxed) - before;
+    println!(
+        "deserialize: {:?} ({}, {} allocations per document)",
+        now.elapsed(),
+        ids,
+        allocs / N
+    );
+}
@@ -0,0 +1,45 @@
+//! Compare the number serialization with `to_string`.
+//!
+//! ```sh
+//! cargo bench --bench num
+//! cargo bench --bench num --features fast-num
+//! ```
+use std::time::Instant;
+use xmlserde::XmlValue;
+
+const N: u64 = 5_000_000;
+
+fn main() {
+    let now = Instant::now();
+    let mut len = 0;
+    for i in 0..N {
+        len += (i * 7919).to_string().len();
+    }
+    println!("to_string:      {:?} ({})", now.elapsed(), len);
+
+    let now = Instant::now();
+    let mut buf = String::new();
+    let mut len = 0;
+    for i in 0..N {
+        buf.clear();
+        (i * 7919).serialize_into(&mut buf);
+        len += buf.len();
+    }
+    println!("serialize_into: {:?} ({})", now.elapsed(), len);
+
+    let now = Instant::now();
+    let mut len = 0;
+    for i in 0..N {
+        len += (i as f64 / 7.0).to_string().len();
+    }
+    println!("f64 to_string:      {:?} ({})", now.elapsed(), len);
+
+    let now = Instant::now();
+    let mut len = 0;
+    for i in 0..N {
+        buf.clear();
+        (i as f64 / 7.0).serialize_into(&mut buf);
+        len += buf.len();
+    }
+    println!("f64 serialize_into: {:?} ({})", now.elapsed(), len);
+}
@@ -1,8 +1,14 @@
 use crate::symbol::{
-    DEFAULT, DENY_UNKNOWN, NAME, ROOT, SKIP_SERIALIZING, TYPE, VEC_SIZE, WITH_CUSTOM_NS, WITH_NS,
-    XML_SERDE,
+    ALIAS, BARE_BOOL, BOOL_FORMAT, CAPACITY, CASE_INSENSITIVE_NAMES, DEFAULT, DENY_DUPLICATE,
+    DENY_UNKNOWN, DESERIALIZE_WITH, EMIT_EMPTY_ON_NONE, ENCODE, EXPAND_EMPTY, FLATTEN, FROM_ATTR,
+    IGNORE_PREFIX, INLINE, KV_SEP, MAP_SEP, NAME, NAME_DE, NAME_SER, NS, ON_MISSING, ON_UNKNOWN,
+    PREFIX, PRESERVE_WHITESPACE, RADIX, RENAME_ALL, ROOT, SELECTABLE, SEP, SERIALIZE_WITH,
+    SKIP_ERRORS, SKIP_SERIALIZING, SKIP_SERIALIZING_IF, SKIP_SERIALIZING_IF_FN, SORT_BY, TRIM,
+    TYPE, TYPE_ATTR, TYPE_VALUE, UPPER, VAL, VEC_SIZE, WIDTH, WITH, WITH_CUSTOM_NS, WITH_NS,
+    XML_SERDE, XSD,
 };
 use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use syn::ext::IdentExt;
 use syn::parse::{self, Parse};
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
@@ -16,8 +22,27 @@ pub struct Container<'a> {
     pub original: &'a syn::DeriveInput,
     pub with_ns: Option<syn::LitByteStr>,
     pub custom_ns: Vec<(syn::LitByteStr, syn::LitByteStr)>,
-    pub root: Option<syn::LitByteStr>,
+    /// A byte string literal or a path to a `&'static [u8]` constant.
+    pub root: Option<syn::Expr>,
     pub deny_unknown: bool,
+    /// Reject a non-`Vec` child or an attribute that occurs more than once.
+    pub deny_duplicate: bool,
+    pub on_unknown: UnknownPolicy,
+    pub on_missing: MissingPolicy,
+    /// Match the tags and the attribute names in ASCII case-insensitive way when deserializing.
+    pub case_insensitive_names: bool,
+    /// Match the tags of the children by their local names without the prefixes when deserializing.
+    pub ignore_prefix: bool,
+    /// The attribute selecting the variant of an enum, like `xsi:type`.
+    pub type_attr: Option<syn::LitByteStr>,
+    /// Generate `serialize_selected` writing only the given fields.
+    pub selectable: bool,
+    /// Derive the names of the fields without a `name` from their idents.
+    pub rename_all: Option<RenameRule>,
+    /// Mark the generated `serialize` and `deserialize` with `#[inline]`.
+    pub inline: bool,
+    /// Write an element without content as `<a></a>` instead of `<a/>`.
+    pub expand_empty: bool,
 }
 
 impl<'a> Container<'a> {
@@ -25,22 +50,138 @@ impl<'a> Container<'a> {
         self.enum_variants.len() > 0
     }
 
+    /// The `#[inline]` attribute of the generated methods if the container asks for it.
+    pub fn inline_attr(&self) -> TokenStream {
+        if self.inline {
+            quote! {#[inline]}
+        } else {
+            quote! {}
+        }
+    }
+
     pub fn validate(&self) {
         if self.root.is_some() && self.is_enum() {
             panic!("for clarity, enum should not have the root attribute. please use a struct to wrap the enum and set its type to untag")
         }
+        if self.deny_duplicate && self.is_enum() {
+            panic!("`deny_duplicate_fields` is not supported in enum type")
+        }
         if self.deny_unknown && self.is_enum() {
             panic!("`deny_unknown_fields` is not supported in enum type")
         }
+        if !matches!(self.on_unknown, UnknownPolicy::Skip) && !self.is_enum() {
+            panic!("`on_unknown` is only supported in enum type, please use `deny_unknown_fields` for struct")
+        }
+        if self.case_insensitive_names && self.is_enum() {
+            panic!("`case_insensitive_names` is not supported in enum type")
+        }
+        if self.expand_empty && self.is_enum() {
+            panic!("`expand_empty` is not supported in enum type")
+        }
+        if self.ignore_prefix && self.is_enum() {
+            panic!("`ignore_prefix` is not supported in enum type")
+        }
+        if matches!(self.on_missing, MissingPolicy::Default) && !self.is_enum() {
+            panic!("`on_missing` is only supported in enum type")
+        }
+        if self.rename_all.is_some() && self.is_enum() {
+            panic!("`rename_all` is not supported in enum type")
+        }
+        if self.selectable
+            && self
+                .struct_fields
+                .iter()
+                .any(|f| matches!(f.ty, EleType::Flatten))
+        {
+            panic!("`selectable` is not supported in a struct with `flatten` fields")
+        }
+        if self.selectable && self.is_enum() {
+            panic!("`selectable` is not supported in enum type")
+        }
+        if self.type_attr.is_some() && !self.is_enum() {
+            panic!("`type_attr` is only supported in enum type")
+        }
+        if self.type_attr.is_none() && self.enum_variants.iter().any(|v| v.type_value.is_some()) {
+            panic!("`type_value` requires the enum to declare `type_attr`")
+        }
+        let any_variants = self
+            .enum_variants
+            .iter()
+            .filter(|v| matches!(v.ele_type, EleType::Any))
+            .count();
+        match (&self.on_unknown, any_variants) {
+            (UnknownPolicy::Capture, 1) | (UnknownPolicy::Skip | UnknownPolicy::Error, 0) => {}
+            (UnknownPolicy::Capture, _) => {
+                panic!(
+                    "`on_unknown = \"capture\"` requires exactly one variant with `ty = \"any\"`"
+                )
+            }
+            _ => panic!("the variant with `ty = \"any\"` requires `on_unknown = \"capture\"`"),
+        }
 
         self.struct_fields.iter().for_each(|f| f.validate());
+        if self
+            .struct_fields
+            .iter()
+            .filter(|f| matches!(f.ty, EleType::TagNs))
+            .count()
+            > 1
+        {
+            panic!("a struct can have only one `tag_ns` field")
+        }
+        if self
+            .struct_fields
+            .iter()
+            .filter(|f| matches!(f.ty, EleType::TagName))
+            .count()
+            > 1
+        {
+            panic!("a struct can have only one `tag_name` field")
+        }
+        if self
+            .struct_fields
+            .iter()
+            .filter(|f| matches!(f.ty, EleType::RestAttrsJoined | EleType::AttrMap))
+            .count()
+            > 1
+        {
+            panic!("a struct can have only one `rest_attrs_joined` or `attr_map` field")
+        }
+        if self
+            .struct_fields
+            .iter()
+            .filter(|f| matches!(f.ty, EleType::Any))
+            .count()
+            > 1
+        {
+            panic!("a struct can have only one `any` field")
+        }
+        if self
+            .struct_fields
+            .iter()
+            .filter(|f| matches!(f.ty, EleType::AttrOrder))
+            .count()
+            > 1
+        {
+            panic!("a struct can have only one `attr_order` field")
+        }
     }
 
     pub fn from_ast(item: &'a syn::DeriveInput, _derive: Derive) -> Container<'a> {
         let mut with_ns = Option::<syn::LitByteStr>::None;
         let mut custom_ns = Vec::<(syn::LitByteStr, syn::LitByteStr)>::new();
-        let mut root = Option::<syn::LitByteStr>::None;
+        let mut root = Option::<syn::Expr>::None;
         let mut deny_unknown = false;
+        let mut deny_duplicate = false;
+        let mut on_unknown = UnknownPolicy::Skip;
+        let mut on_missing = MissingPolicy::Error;
+        let mut case_insensitive_names = false;
+        let mut ignore_prefix = false;
+        let mut inline = false;
+        let mut expand_empty = false;
+        let mut selectable = false;
+        let mut rename_all = Option::<RenameRule>::None;
+        let mut type_attr = Option::<syn::LitByteStr>::None;
         for meta_item in item
             .attrs
             .iter()
@@ -53,13 +194,62 @@ impl<'a> Container<'a> {
                         with_ns = Some(s.clone());
                     }
                 }
+                NameValue(m) if m.path == TYPE_ATTR => {
+                    let s = get_lit_byte_str(&m.value).expect("parse type_attr failed");
+                    type_attr = Some(s.clone());
+                }
                 NameValue(m) if m.path == ROOT => {
-                    let s = get_lit_byte_str(&m.value).expect("parse root failed");
-                    root = Some(s.clone());
+                    root = Some(parse_root(&m.value));
                 }
                 Meta::Path(p) if p == DENY_UNKNOWN => {
                     deny_unknown = true;
                 }
+                Meta::Path(p) if p == DENY_DUPLICATE => {
+                    deny_duplicate = true;
+                }
+                Meta::Path(p) if p == CASE_INSENSITIVE_NAMES => {
+                    case_insensitive_names = true;
+                }
+                Meta::Path(p) if p == EXPAND_EMPTY => {
+                    expand_empty = true;
+                }
+                Meta::Path(p) if p == INLINE => {
+                    inline = true;
+                }
+                Meta::Path(p) if p == IGNORE_PREFIX => {
+                    ignore_prefix = true;
+                }
+                Meta::Path(p) if p == SELECTABLE => {
+                    selectable = true;
+                }
+                NameValue(m) if m.path == RENAME_ALL => {
+                    let s = get_lit_str(&m.value).expect("parse rename_all failed");
+                    rename_all = Some(match s.value().as_str() {
+                        "camelCase" => RenameRule::CamelCase,
+                        "PascalCase" => RenameRule::PascalCase,
+                        "kebab-case" => RenameRule::KebabCase,
+                        "snake_case" => RenameRule::SnakeCase,
+                        _ => panic!("invalid rename_all, should be `camelCase`, `PascalCase`, `kebab-case` or `snake_case`"),
+                    });
+                }
+                NameValue(m) if m.path == ON_UNKNOWN => {
+                    let s = get_lit_str(&m.value).expect("parse on_unknown failed");
+                    on_unknown = match s.value().as_str() {
+                        "skip" => UnknownPolicy::Skip,
+                        "error" => UnknownPolicy::Error,
+                        "capture" => UnknownPolicy::Capture,
+                        _ => panic!("invalid on_unknown, should be `skip`, `error` or `capture`"),
+                    };
+                }
+                NameValue(m) if m.path == ON_MISSING => {
+                    let s = get_lit_str(&m.value).expect("parse on_missing failed");
+                    on_missing = match s.value().as_str() {
+                        // `panic` is the deprecated name of `error`.
+                        "error" | "panic" => MissingPolicy::Error,
+                        "default" => MissingPolicy::Default,
+                        _ => panic!("invalid on_missing, should be `error` or `default`"),
+                    };
+                }
                 Meta::List(l) if l.path == WITH_CUSTOM_NS => {
                     let strs = l
                         .parse_args_with(Punctuated::<syn::LitByteStr, Comma>::parse_terminated)
@@ -80,9 +270,17 @@ impl<'a> Container<'a> {
                 let fields = ds
                     .fields
                     .iter()
-                    .map(|f| StructField::from_ast(f))
+                    .map(|f| StructField::from_ast(f, rename_all.as_ref()))
                     .filter(|f| f.is_some())
                     .map(|f| f.unwrap())
+                    .map(|mut f| {
+                        if ignore_prefix
+                            && matches!(f.ty, EleType::Child | EleType::SelfClosedChild)
+                        {
+                            f.ignore_prefix = true;
+                        }
+                        f
+                    })
                     .collect::<Vec<_>>();
                 Container {
                     struct_fields: fields,
@@ -92,6 +290,16 @@ impl<'a> Container<'a> {
                     custom_ns,
                     root,
                     deny_unknown,
+                    deny_duplicate,
+                    on_unknown,
+                    on_missing,
+                    case_insensitive_names,
+                    ignore_prefix,
+                    type_attr: type_attr.clone(),
+                    selectable,
+                    rename_all,
+                    inline,
+                    expand_empty,
                 }
             }
             syn::Data::Enum(e) => {
@@ -108,6 +316,16 @@ impl<'a> Container<'a> {
                     custom_ns,
                     root,
                     deny_unknown,
+                    deny_duplicate,
+                    on_unknown,
+                    on_missing,
+                    case_insensitive_names,
+                    ignore_prefix,
+                    type_attr: type_attr.clone(),
+                    selectable,
+                    rename_all,
+                    inline,
+                    expand_empty,
                 }
             }
             syn::Data::Union(_) => panic!("Only support struct and enum type, union is found"),
@@ -122,6 +340,13 @@ pub struct FieldsSummary<'a> {
     pub self_closed_children: Vec<StructField<'a>>,
     pub untagged_enums: Vec<StructField<'a>>,
     pub untagged_structs: Vec<StructField<'a>>,
+    pub tag_ns: Option<StructField<'a>>,
+    pub tag_name: Option<StructField<'a>>,
+    pub rest_attrs: Option<StructField<'a>>,
+    pub attr_map: Option<StructField<'a>>,
+    pub any: Option<StructField<'a>>,
+    pub attr_order: Option<StructField<'a>>,
+    pub flattens: Vec<StructField<'a>>,
 }
 
 impl<'a> FieldsSummary<'a> {
@@ -133,6 +358,13 @@ impl<'a> FieldsSummary<'a> {
             self_closed_children: vec![],
             untagged_enums: vec![],
             untagged_structs: vec![],
+            tag_ns: None,
+            tag_name: None,
+            rest_attrs: None,
+            attr_map: None,
+            any: None,
+            attr_order: None,
+            flattens: vec![],
         };
         fields.into_iter().for_each(|f| match f.ty {
             EleType::Attr => result.attrs.push(f),
@@ -142,6 +374,13 @@ impl<'a> FieldsSummary<'a> {
             EleType::Untag => result.untagged_enums.push(f),
             EleType::UntaggedEnum => result.untagged_enums.push(f),
             EleType::UntaggedStruct => result.untagged_structs.push(f),
+            EleType::TagNs => result.tag_ns = Some(f),
+            EleType::TagName => result.tag_name = Some(f),
+            EleType::RestAttrsJoined => result.rest_attrs = Some(f),
+            EleType::AttrMap => result.attr_map = Some(f),
+            EleType::Any => result.any = Some(f),
+            EleType::AttrOrder => result.attr_order = Some(f),
+            EleType::Flatten => result.flattens.push(f),
         });
         result
     }
@@ -150,11 +389,66 @@ impl<'a> FieldsSummary<'a> {
 pub struct StructField<'a> {
     pub ty: EleType,
     pub name: Option<syn::LitByteStr>,
+    /// Other names accepted when deserializing.
+    pub aliases: Vec<syn::LitByteStr>,
     pub skip_serializing: bool,
+    /// A function taking a reference to the field and telling whether to skip serializing it.
+    pub skip_serializing_if: Option<syn::ExprPath>,
+    /// A function taking a reference to the whole struct and telling whether to skip serializing the field.
+    pub skip_serializing_if_fn: Option<syn::ExprPath>,
+    /// Write `None` of an `Option` child as an empty element, which is read back as `None`.
+    pub emit_empty_on_none: bool,
     pub default: Option<syn::ExprPath>,
     pub original: &'a syn::Field,
     pub vec_size: Option<syn::Lit>,
+    /// The initial capacity of an `attr_map` or an `any` field, in the same form as `vec_size`.
+    pub capacity: Option<syn::Lit>,
     pub generic: Generic<'a>,
+    /// Serialize the value in the lexical form of XSD `decimal`.
+    pub xsd_decimal: bool,
+    /// The separator of the items in a list `attr`.
+    pub sep: Option<syn::LitStr>,
+    /// The separators of the pairs and of the key and the value in a map `attr`, like `;` and `:`.
+    pub map_sep: Option<(syn::LitStr, syn::LitStr)>,
+    /// Pass the whitespace-only text to the untagged enum instead of dropping it.
+    pub preserve_whitespace: bool,
+    /// The namespace of an `attr`, a `child` or an `sfc`. It is matched by the namespace and its local name.
+    pub ns: Option<syn::LitByteStr>,
+    /// The prefix written before the name of a field with `ns`. Without `ns`, it is
+    /// merged into `name` when parsing the field.
+    pub prefix: Option<syn::LitByteStr>,
+    /// Drop the elements of a `Vec` child that fail to deserialize.
+    pub skip_errors: bool,
+    /// The module providing `serialize` and `deserialize` of an `attr` or a `text`.
+    pub with: Option<syn::ExprPath>,
+    /// The function serializing an `attr` or a `text`, like `fn(&T) -> String`.
+    pub serialize_with: Option<syn::ExprPath>,
+    /// The function deserializing an `attr` or a `text`, like `fn(&str) -> Result<T, String>`.
+    pub deserialize_with: Option<syn::ExprPath>,
+    /// Encode the bytes of an `attr` or a `text` in base64.
+    pub encode_base64: bool,
+    /// Write an integer `attr` or `text` in this radix, which is 2, 8 or 16.
+    pub radix: Option<u32>,
+    /// The minimum number of digits, padded with zeros.
+    pub width: Option<usize>,
+    /// Use the uppercase hexadecimal digits.
+    pub upper: bool,
+    /// The attribute holding the value of an `sfc`, like `<b val="0"/>`.
+    pub val: Option<syn::LitByteStr>,
+    /// Write a boolean `attr` or `text` as `true`/`false` instead of `1`/`0`.
+    pub true_false: bool,
+    /// Write a boolean `attr` as a bare name like `<option selected>` when it is `true`, and omit it when it is `false`.
+    pub bare_bool: bool,
+    /// The attribute of a `child` holding its value, like `value` in `<dimension value="5"/>`.
+    pub from_attr: Option<syn::LitByteStr>,
+    /// Match the tag of a `child` or an `sfc` by its local name without the prefix when deserializing.
+    pub ignore_prefix: bool,
+    /// The field of the elements by which a `Vec` child is sorted when serializing.
+    pub sort_by: Option<syn::Ident>,
+    /// Trim the XML whitespaces around a `text` or the text of an untagged enum before deserializing it.
+    pub trim: bool,
+    /// The name written when serializing if it differs from `name`, which is then only read when deserializing.
+    pub name_ser: Option<syn::LitByteStr>,
 }
 
 impl<'a> StructField<'a> {
@@ -168,27 +462,312 @@ impl<'a> StructField<'a> {
         if untagged && self.name.is_some() {
             panic!("untagged types doesn't need a name")
         }
+        if self.xsd_decimal && !matches!(self.ty, EleType::Attr | EleType::Text) {
+            panic!("`xsd` is only supported in `attr` and `text`")
+        }
+        if let Some(t) = self.generic.get_vec() {
+            if matches!(get_generics(t), Generic::Vec(_)) {
+                panic!("nested vector is not supported, please wrap the inner vector in a struct")
+            }
+        }
+        // An `attr` or a `text` with its own codec, like `Option<Vec<u8>>` with `encode = "base64"`,
+        // reads the `Vec` as a single value.
+        let single_value = self.encode_base64
+            || self.sep.is_some()
+            || self.with.is_some()
+            || self.serialize_with.is_some()
+            || self.deserialize_with.is_some();
+        if self.generic.get_opt_vec().is_some() && !single_value {
+            if !matches!(self.ty, EleType::Child) {
+                panic!("`Option<Vec<T>>` is only supported in `child`")
+            }
+            if self.from_attr.is_some() || self.emit_empty_on_none {
+                panic!("`Option<Vec<T>>` cannot be used with `from_attr` or `emit_empty_on_none`")
+            }
+        }
+        if self.sep.is_some() {
+            if !matches!(self.ty, EleType::Attr) {
+                panic!("`sep` is only supported in `attr`")
+            }
+            if !self.generic.is_vec() && self.get_array().is_none() && self.get_tuple().is_none() {
+                panic!("`sep` requires the type to be `Vec<T>`, `[T; N]` or a tuple")
+            }
+        }
+        if !self.aliases.is_empty() {
+            if !matches!(self.ty, EleType::Child | EleType::Attr) {
+                panic!("`alias` is only supported in `child` and `attr`")
+            }
+            if self.name.is_none() {
+                panic!("`alias` requires a `name`")
+            }
+        }
+        if self.with.is_some() {
+            if !matches!(self.ty, EleType::Attr | EleType::Text) {
+                panic!("`with` is only supported in `attr` and `text`")
+            }
+            if self.sep.is_some() || self.xsd_decimal {
+                panic!("`with` cannot be used with `sep` or `xsd`")
+            }
+        }
+        if self.map_sep.is_some() {
+            if !matches!(self.ty, EleType::Attr) {
+                panic!("`map_sep` and `kv_sep` are only supported in `attr`")
+            }
+            if self.with.is_some()
+                || self.sep.is_some()
+                || self.xsd_decimal
+                || self.encode_base64
+                || self.radix.is_some()
+            {
+                panic!("`map_sep` cannot be used with `with`, `sep`, `xsd`, `encode` or `radix`")
+            }
+        }
+        if self.serialize_with.is_some() || self.deserialize_with.is_some() {
+            if !matches!(self.ty, EleType::Attr | EleType::Text) {
+                panic!("`serialize_with` and `deserialize_with` are only supported in `attr` and `text`")
+            }
+            if self.with.is_some()
+                || self.sep.is_some()
+                || self.xsd_decimal
+                || self.encode_base64
+                || self.radix.is_some()
+                || self.map_sep.is_some()
+            {
+                panic!("`serialize_with` and `deserialize_with` cannot be used with `with`, `sep`, `xsd`, `encode` or `radix`")
+            }
+        }
+        if self.encode_base64 {
+            if !matches!(self.ty, EleType::Attr | EleType::Text) {
+                panic!("`encode` is only supported in `attr` and `text`")
+            }
+            if self.with.is_some() || self.sep.is_some() || self.xsd_decimal {
+                panic!("`encode` cannot be used with `with`, `sep` or `xsd`")
+            }
+        }
+        if let Some(radix) = self.radix {
+            if !matches!(self.ty, EleType::Attr | EleType::Text) {
+                panic!("`radix` is only supported in `attr` and `text`")
+            }
+            if !matches!(radix, 2 | 8 | 16) {
+                panic!("invalid radix, should be 2, 8 or 16")
+            }
+            if self.with.is_some() || self.sep.is_some() || self.xsd_decimal || self.encode_base64 {
+                panic!("`radix` cannot be used with `with`, `sep`, `xsd` or `encode`")
+            }
+        } else if self.width.is_some() || self.upper {
+            panic!("`width` and `upper` require `radix`")
+        }
+        if self.bare_bool {
+            if !matches!(self.ty, EleType::Attr) {
+                panic!("`bare_bool` is only supported in `attr`")
+            }
+            if !matches!(self.generic, Generic::None) {
+                panic!("`bare_bool` requires the type to be `bool`")
+            }
+            if self.with.is_some()
+                || self.serialize_with.is_some()
+                || self.deserialize_with.is_some()
+                || self.true_false
+            {
+                panic!("`bare_bool` cannot be used with `with`, `serialize_with`, `deserialize_with` or `bool_format`")
+            }
+        }
+        if self.true_false {
+            if !matches!(self.ty, EleType::Attr | EleType::Text) {
+                panic!("`bool_format` is only supported in `attr` and `text`")
+            }
+            if self.with.is_some() || self.serialize_with.is_some() || self.sep.is_some() {
+                panic!("`bool_format` cannot be used with `with`, `serialize_with` or `sep`")
+            }
+        }
+        if (self.skip_serializing_if.is_some() || self.skip_serializing_if_fn.is_some())
+            && matches!(
+                self.ty,
+                EleType::Untag | EleType::UntaggedEnum | EleType::UntaggedStruct
+            )
+        {
+            panic!("`skip_serializing_if` and `skip_serializing_if_fn` are not supported in untagged fields")
+        }
+        if matches!(self.ty, EleType::TagNs | EleType::TagName) {
+            if self.name.is_some() {
+                panic!("`tag_ns` and `tag_name` don't need a name")
+            }
+            if self.generic.is_vec() {
+                panic!("`tag_ns` and `tag_name` should be `String` or `Option<String>`")
+            }
+        }
+        if self.emit_empty_on_none && (!matches!(self.ty, EleType::Child) || !self.generic.is_opt())
+        {
+            panic!("`emit_empty_on_none` is only supported in `Option` child")
+        }
+        if matches!(self.ty, EleType::Flatten) {
+            if self.name.is_some() {
+                panic!("`flatten` doesn't need a name")
+            }
+            if !matches!(self.generic, Generic::None) {
+                panic!("`flatten` is not supported in `Option` or `Vec`")
+            }
+        }
+        if matches!(self.ty, EleType::Any) {
+            if self.name.is_some() {
+                panic!("`any` doesn't need a name")
+            }
+            if !self.generic.is_vec() {
+                panic!("`any` should be `Vec<Unparsed>`")
+            }
+        }
+        if self.capacity.is_some() && !matches!(self.ty, EleType::AttrMap | EleType::Any) {
+            panic!("`capacity` is only supported in `attr_map` and `any`")
+        }
+        if matches!(self.ty, EleType::AttrOrder) {
+            if self.name.is_some() {
+                panic!("`attr_order` doesn't need a name")
+            }
+            if !self.generic.is_vec() {
+                panic!("`attr_order` should be `Vec<String>`")
+            }
+        }
+        if matches!(self.ty, EleType::RestAttrsJoined | EleType::AttrMap) {
+            if self.name.is_some() {
+                panic!("`rest_attrs_joined` and `attr_map` don't need a name")
+            }
+            if !matches!(self.generic, Generic::None) {
+                panic!("`rest_attrs_joined` should be `String` and `attr_map` should be a map")
+            }
+        }
+        if self.val.is_some() && !matches!(self.ty, EleType::SelfClosedChild) {
+            panic!("`val` is only supported in `sfc`")
+        }
+        if self.ignore_prefix && !matches!(self.ty, EleType::Child | EleType::SelfClosedChild) {
+            panic!("`ignore_prefix` is only supported in `child` and `sfc`")
+        }
+        if self.from_attr.is_some() {
+            if !matches!(self.ty, EleType::Child) {
+                panic!("`from_attr` is only supported in `child`")
+            }
+            if self.skip_errors || self.emit_empty_on_none {
+                panic!("`from_attr` cannot be used with `skip_errors` or `emit_empty_on_none`")
+            }
+        }
+        if self.sort_by.is_some() {
+            if !matches!(self.ty, EleType::Child) || !self.generic.is_vec() {
+                panic!("`sort_by` is only supported in `Vec` child")
+            }
+            if self.from_attr.is_some() {
+                panic!("`sort_by` cannot be used with `from_attr`")
+            }
+        }
+        if self.skip_errors && (!matches!(self.ty, EleType::Child) || !self.generic.is_vec()) {
+            panic!("`skip_errors` is only supported in `Vec` child")
+        }
+        if self.ns.is_some()
+            && !matches!(
+                self.ty,
+                EleType::Attr | EleType::Child | EleType::SelfClosedChild
+            )
+        {
+            panic!("`ns` is only supported in `attr`, `child` and `sfc`")
+        }
+        if self.prefix.is_some()
+            && !matches!(
+                self.ty,
+                EleType::Attr | EleType::Child | EleType::SelfClosedChild
+            )
+        {
+            panic!("`prefix` is only supported in `attr`, `child` and `sfc`")
+        }
+        if self.preserve_whitespace {
+            if !matches!(self.ty, EleType::Untag | EleType::UntaggedEnum) || !self.generic.is_vec()
+            {
+                panic!("`preserve_whitespace` is only supported in untagged `Vec<Enum>`")
+            }
+        }
+        if self.name_ser.is_some() {
+            if !matches!(
+                self.ty,
+                EleType::Attr | EleType::Child | EleType::SelfClosedChild
+            ) {
+                panic!("`name_ser` is only supported in `attr`, `child` and `sfc`")
+            }
+            if self.name.is_none() {
+                panic!("`name_ser` requires `name` or `name_de`")
+            }
+        }
+        if self.trim {
+            if !matches!(
+                self.ty,
+                EleType::Text | EleType::Untag | EleType::UntaggedEnum
+            ) {
+                panic!("`trim` is only supported in `text` and untagged enums")
+            }
+            if self.preserve_whitespace {
+                panic!("`trim` cannot be used with `preserve_whitespace`")
+            }
+        }
     }
 
-    pub fn from_ast(f: &'a syn::Field) -> Option<Self> {
+    pub fn from_ast(f: &'a syn::Field, rename_all: Option<&RenameRule>) -> Option<Self> {
         let mut name = Option::<syn::LitByteStr>::None;
+        let mut aliases = Vec::<syn::LitByteStr>::new();
         let mut skip_serializing = false;
+        let mut skip_serializing_if = Option::<syn::ExprPath>::None;
+        let mut skip_serializing_if_fn = Option::<syn::ExprPath>::None;
+        let mut emit_empty_on_none = false;
         let mut default = Option::<syn::ExprPath>::None;
         let mut ty = Option::<EleType>::None;
         let mut vec_size = Option::<syn::Lit>::None;
-        let generic = get_generics(&f.ty);
+        let mut capacity = Option::<syn::Lit>::None;
+        let mut xsd_decimal = false;
+        let mut sep = Option::<syn::LitStr>::None;
+        let mut map_sep = Option::<syn::LitStr>::None;
+        let mut kv_sep = Option::<syn::LitStr>::None;
+        let mut preserve_whitespace = false;
+        let mut ns = Option::<syn::LitByteStr>::None;
+        let mut prefix = Option::<syn::LitByteStr>::None;
+        let mut skip_errors = false;
+        let mut with = Option::<syn::ExprPath>::None;
+        let mut serialize_with = Option::<syn::ExprPath>::None;
+        let mut deserialize_with = Option::<syn::ExprPath>::None;
+        let mut encode_base64 = false;
+        let mut radix = Option::<u32>::None;
+        let mut true_false = false;
+        let mut bare_bool = false;
+        let mut width = Option::<usize>::None;
+        let mut upper = false;
+        let mut val = Option::<syn::LitByteStr>::None;
+        let mut from_attr = Option::<syn::LitByteStr>::None;
+        let mut ignore_prefix = false;
+        let mut sort_by = Option::<syn::Ident>::None;
+        let mut trim = false;
+        let mut name_de = Option::<syn::LitByteStr>::None;
+        let mut name_ser = Option::<syn::LitByteStr>::None;
+        let mut generic = get_generics(&f.ty);
+        let mut has_meta = false;
         for meta_item in f
             .attrs
             .iter()
             .flat_map(|attr| get_xmlserde_meta_items(attr))
             .flatten()
         {
+            has_meta = true;
             match meta_item {
                 NameValue(m) if m.path == NAME => {
                     if let Ok(s) = get_lit_byte_str(&m.value) {
                         name = Some(s.clone());
                     }
                 }
+                NameValue(m) if m.path == NAME_DE => {
+                    let s = get_lit_byte_str(&m.value).expect("parse name_de failed");
+                    name_de = Some(s.clone());
+                }
+                NameValue(m) if m.path == NAME_SER => {
+                    let s = get_lit_byte_str(&m.value).expect("parse name_ser failed");
+                    name_ser = Some(s.clone());
+                }
+                NameValue(m) if m.path == ALIAS => {
+                    let s = get_lit_byte_str(&m.value).expect("parse alias failed");
+                    aliases.push(s.clone());
+                }
                 NameValue(m) if m.path == TYPE => {
                     if let Ok(s) = get_lit_str(&m.value) {
                         let t = match s.value().as_str() {
@@ -199,26 +778,150 @@ impl<'a> StructField<'a> {
                             "untag" => EleType::Untag, // todo: generate a deprecate function to let users know
                             "untagged_enum" => EleType::UntaggedEnum,
                             "untagged_struct" => EleType::UntaggedStruct,
+                            "tag_ns" => EleType::TagNs,
+                            "tag_name" => EleType::TagName,
+                            "rest_attrs_joined" => EleType::RestAttrsJoined,
+                            "attr_map" => EleType::AttrMap,
+                            "any" => EleType::Any,
+                            "attr_order" => EleType::AttrOrder,
                             _ => panic!("invalid type"),
                         };
+                        match ty {
+                            Some(EleType::Flatten) => panic!("`flatten` doesn't need a `ty`"),
+                            Some(_) => panic!(
+                                "duplicate `ty` on field `{}`",
+                                f.ident.as_ref().map(|i| i.to_string()).unwrap_or_default()
+                            ),
+                            None => {}
+                        }
                         ty = Some(t);
                     }
                 }
+                Path(word) if word == FLATTEN => {
+                    if ty.is_some() {
+                        panic!("`flatten` doesn't need a `ty`")
+                    }
+                    ty = Some(EleType::Flatten);
+                }
                 NameValue(m) if m.path == VEC_SIZE => {
-                    if let syn::Expr::Lit(lit) = m.value {
-                        match lit.lit {
-                            syn::Lit::Str(_) | syn::Lit::Int(_) => {
-                                vec_size = Some(lit.lit);
-                            }
-                            _ => panic!(),
-                        }
-                    } else {
-                        panic!()
+                    vec_size = Some(parse_size_lit(m.value));
+                }
+                NameValue(m) if m.path == CAPACITY => {
+                    capacity = Some(parse_size_lit(m.value));
+                }
+                NameValue(m) if m.path == XSD => {
+                    let s = get_lit_str(&m.value).expect("parse xsd failed");
+                    match s.value().as_str() {
+                        "decimal" => xsd_decimal = true,
+                        _ => panic!("invalid xsd, only `decimal` is supported"),
+                    }
+                }
+                NameValue(m) if m.path == ENCODE => {
+                    let s = get_lit_str(&m.value).expect("parse encode failed");
+                    match s.value().as_str() {
+                        "base64" => encode_base64 = true,
+                        _ => panic!("invalid encode, only `base64` is supported"),
                     }
                 }
+                NameValue(m) if m.path == BOOL_FORMAT => {
+                    let s = get_lit_str(&m.value).expect("parse bool_format failed");
+                    true_false = match s.value().as_str() {
+                        "true_false" => true,
+                        "one_zero" => false,
+                        _ => panic!("invalid bool_format, should be `true_false` or `one_zero`"),
+                    };
+                }
+                NameValue(m) if m.path == RADIX => {
+                    let i = get_lit_int(&m.value).expect("parse radix failed");
+                    radix = Some(i.base10_parse().expect("parse radix failed"));
+                }
+                NameValue(m) if m.path == WIDTH => {
+                    let i = get_lit_int(&m.value).expect("parse width failed");
+                    width = Some(i.base10_parse().expect("parse width failed"));
+                }
+                NameValue(m) if m.path == VAL => {
+                    let s = get_lit_byte_str(&m.value).expect("parse val failed");
+                    val = Some(s.clone());
+                }
+                Path(word) if word == UPPER => {
+                    upper = true;
+                }
+                Path(word) if word == BARE_BOOL => {
+                    bare_bool = true;
+                }
+                NameValue(m) if m.path == NS => {
+                    let s = get_lit_byte_str(&m.value).expect("parse ns failed");
+                    ns = Some(s.clone());
+                }
+                NameValue(m) if m.path == PREFIX => {
+                    let s = get_lit_byte_str(&m.value).expect("parse prefix failed");
+                    prefix = Some(s.clone());
+                }
+                NameValue(m) if m.path == MAP_SEP => {
+                    let s = get_lit_str(&m.value).expect("parse map_sep failed");
+                    map_sep = Some(s.clone());
+                }
+                NameValue(m) if m.path == KV_SEP => {
+                    let s = get_lit_str(&m.value).expect("parse kv_sep failed");
+                    kv_sep = Some(s.clone());
+                }
+                NameValue(m) if m.path == SEP => {
+                    let s = get_lit_str(&m.value).expect("parse sep failed");
+                    sep = Some(s.clone());
+                }
                 Path(word) if word == SKIP_SERIALIZING => {
                     skip_serializing = true;
                 }
+                Path(word) if word == SKIP_ERRORS => {
+                    skip_errors = true;
+                }
+                Path(word) if word == PRESERVE_WHITESPACE => {
+                    preserve_whitespace = true;
+                }
+                NameValue(m) if m.path == WITH => {
+                    let path = parse_lit_into_expr_path(&m.value).expect("parse with path");
+                    with = Some(path);
+                }
+                NameValue(m) if m.path == FROM_ATTR => {
+                    let s = get_lit_byte_str(&m.value).expect("parse from_attr failed");
+                    from_attr = Some(s.clone());
+                }
+                Path(word) if word == IGNORE_PREFIX => {
+                    ignore_prefix = true;
+                }
+                Path(word) if word == TRIM => {
+                    trim = true;
+                }
+                NameValue(m) if m.path == SORT_BY => {
+                    let s = get_lit_str(&m.value).expect("parse sort_by failed");
+                    let ident = s
+                        .parse::<syn::Ident>()
+                        .expect("sort_by should be a field name");
+                    sort_by = Some(ident);
+                }
+                Path(word) if word == EMIT_EMPTY_ON_NONE => {
+                    emit_empty_on_none = true;
+                }
+                NameValue(m) if m.path == SKIP_SERIALIZING_IF => {
+                    let path =
+                        parse_lit_into_expr_path(&m.value).expect("parse skip_serializing_if path");
+                    skip_serializing_if = Some(path);
+                }
+                NameValue(m) if m.path == SKIP_SERIALIZING_IF_FN => {
+                    let path = parse_lit_into_expr_path(&m.value)
+                        .expect("parse skip_serializing_if_fn path");
+                    skip_serializing_if_fn = Some(path);
+                }
+                NameValue(m) if m.path == SERIALIZE_WITH => {
+                    let path =
+                        parse_lit_into_expr_path(&m.value).expect("parse serialize_with path");
+                    serialize_with = Some(path);
+                }
+                NameValue(m) if m.path == DESERIALIZE_WITH => {
+                    let path =
+                        parse_lit_into_expr_path(&m.value).expect("parse deserialize_with path");
+                    deserialize_with = Some(path);
+                }
                 NameValue(m) if m.path == DEFAULT => {
                     let path = parse_lit_into_expr_path(&m.value)
                         .expect("parse default path")
@@ -229,21 +932,119 @@ impl<'a> StructField<'a> {
             }
         }
         if ty.is_none() {
+            if has_meta {
+                let ident = f.ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
+                panic!(
+                    "missing `ty` in the xmlserde attribute of field `{}`",
+                    ident
+                )
+            }
             None
         } else {
+            let ty = ty.expect("should has a ty");
+            if name_de.is_some() {
+                name = name_de;
+            }
+            if let (None, Some(rule), Some(ident)) = (&name, rename_all, &f.ident) {
+                if matches!(
+                    ty,
+                    EleType::Attr | EleType::Child | EleType::SelfClosedChild
+                ) {
+                    let value = rule.apply(&ident.unraw().to_string());
+                    name = Some(syn::LitByteStr::new(value.as_bytes(), ident.span()));
+                }
+            }
+            if let (Some(p), None) = (&prefix, &ns) {
+                let prefixed = |n: &syn::LitByteStr| {
+                    let mut value = p.value();
+                    value.push(b':');
+                    value.extend(n.value());
+                    syn::LitByteStr::new(&value, n.span())
+                };
+                let n = name.as_ref().expect("`prefix` requires a `name`");
+                name = Some(prefixed(n));
+                name_ser = name_ser.as_ref().map(prefixed);
+            }
+            let map_sep = match (map_sep, kv_sep) {
+                (Some(m), Some(k)) => Some((m, k)),
+                (None, None) => None,
+                _ => panic!("`map_sep` and `kv_sep` should be used together"),
+            };
+            if bare_bool && default.is_none() {
+                // A missing bare attribute is `false`.
+                default = Some(syn::parse_quote!(::std::default::Default::default));
+            }
+            if encode_base64 && generic.is_vec() {
+                // The encoded bytes are a single value rather than a list.
+                generic = Generic::None;
+            }
             Some(StructField {
-                ty: ty.expect("should has a ty"),
+                ty,
                 name,
+                aliases,
                 skip_serializing,
+                skip_serializing_if,
+                skip_serializing_if_fn,
+                emit_empty_on_none,
                 default,
                 original: f,
                 vec_size,
+                capacity,
                 generic,
+                xsd_decimal,
+                sep,
+                map_sep,
+                preserve_whitespace,
+                ns,
+                prefix,
+                skip_errors,
+                with,
+                serialize_with,
+                deserialize_with,
+                encode_base64,
+                radix,
+                true_false,
+                bare_bool,
+                from_attr,
+                ignore_prefix,
+                sort_by,
+                trim,
+                name_ser,
+                width,
+                upper,
+                val,
             })
         }
     }
 
+    /// Get the element type and the length if the type of this field is an array.
+    pub fn get_array(&self) -> Option<(&syn::Type, &syn::Expr)> {
+        match &self.original.ty {
+            syn::Type::Array(a) => Some((&a.elem, &a.len)),
+            _ => None,
+        }
+    }
+
+    /// Get the element types if the field is a tuple of at least 2 elements.
+    pub fn get_tuple(&self) -> Option<Vec<&syn::Type>> {
+        match &self.original.ty {
+            syn::Type::Tuple(t) if t.elems.len() >= 2 => Some(t.elems.iter().collect()),
+            _ => None,
+        }
+    }
+
     pub fn is_required(&self) -> bool {
+        if matches!(
+            self.ty,
+            EleType::TagNs
+                | EleType::TagName
+                | EleType::RestAttrsJoined
+                | EleType::AttrMap
+                | EleType::AttrOrder
+                | EleType::Flatten
+        ) {
+            return false;
+        }
         if matches!(self.ty, EleType::Untag) || matches!(self.ty, EleType::UntaggedEnum) {
             return match self.generic {
                 Generic::Vec(_) => false,
@@ -262,12 +1063,15 @@ pub struct EnumVariant<'a> {
     pub ident: &'a syn::Ident,
     pub ty: Option<&'a syn::Type>,
     pub ele_type: EleType,
+    /// The value of the container's `type_attr` selecting this variant.
+    pub type_value: Option<syn::LitByteStr>,
 }
 
 impl<'a> EnumVariant<'a> {
     pub fn from_ast(v: &'a Variant) -> Self {
         let mut name = Option::<syn::LitByteStr>::None;
         let mut ele_type = EleType::Child;
+        let mut type_value = Option::<syn::LitByteStr>::None;
         for meta_item in v
             .attrs
             .iter()
@@ -285,18 +1089,25 @@ impl<'a> EnumVariant<'a> {
                         let t = match s.value().as_str() {
                             "child" => EleType::Child,
                             "text" => EleType::Text,
-                            _ => panic!("invalid type in enum, should be `text` or `child` only"),
+                            "any" => EleType::Any,
+                            _ => panic!(
+                                "invalid type in enum, should be `text`, `child` or `any` only"
+                            ),
                         };
                         ele_type = t;
                     }
                 }
+                NameValue(m) if m.path == TYPE_VALUE => {
+                    let s = get_lit_byte_str(&m.value).expect("parse type_value failed");
+                    type_value = Some(s.clone());
+                }
                 _ => panic!("unexpected attribute"),
             }
         }
         if v.fields.len() > 1 {
             panic!("only support 1 field");
         }
-        if matches!(ele_type, EleType::Text) {
+        if matches!(ele_type, EleType::Text | EleType::Any) {
             if name.is_some() {
                 panic!("should omit the `name`");
             }
@@ -306,11 +1117,33 @@ impl<'a> EnumVariant<'a> {
         let field = &v.fields.iter().next();
         let ty = field.map(|t| &t.ty);
         let ident = &v.ident;
-        EnumVariant {
+        let result = EnumVariant {
             name,
             ty,
             ident,
             ele_type,
+            type_value,
+        };
+        if result.type_value.is_some()
+            && (result.ty.is_none() || !matches!(result.ele_type, EleType::Child))
+        {
+            panic!("the variant with `type_value` should be a `child` with a field")
+        }
+        if matches!(result.ele_type, EleType::Any) && result.ty.is_none() {
+            panic!("the variant with `ty = \"any\"` should have an `Unparsed` field")
+        }
+        if result.is_transparent() && result.ty.is_none() {
+            panic!("the variant with an empty name should have a field")
+        }
+        result
+    }
+
+    /// A variant whose name is `b""` has no element of its own. It is dispatched by
+    /// the children tags of its inner type.
+    pub fn is_transparent(&self) -> bool {
+        match &self.name {
+            Some(n) => n.value().is_empty(),
+            None => false,
         }
     }
 }
@@ -339,6 +1172,67 @@ pub enum EleType {
 
     UntaggedEnum,
     UntaggedStruct,
+    /// The namespace of the element itself, resolved from the `xmlns` declarations in scope.
+    TagNs,
+    /// The name of the element itself.
+    TagName,
+    /// The attributes matching none of the `attr` fields, joined like `a="1" b="2"`.
+    RestAttrsJoined,
+    /// The attributes matching none of the `attr` fields, collected into a map.
+    AttrMap,
+    /// The children matching none of the fields, captured as `Unparsed`.
+    Any,
+    /// The names of the attributes in the order they appear in the source.
+    AttrOrder,
+    /// A struct whose attributes and children are inlined in this element.
+    Flatten,
+}
+
+/// Specify what an enum does when meeting a child that matches none of its variants.
+pub enum UnknownPolicy {
+    Skip,
+    Error,
+    /// Keep the child in the variant declared with `ty = "any"`.
+    Capture,
+}
+
+/// Specify what an enum does when none of its variants is found.
+pub enum MissingPolicy {
+    /// Return `XmlDeError::MissingVariant`.
+    Error,
+    /// Use `Default::default()` of the enum.
+    Default,
+}
+
+/// The case conversion from the snake_case field idents to the names in the xml.
+pub enum RenameRule {
+    CamelCase,
+    PascalCase,
+    KebabCase,
+    SnakeCase,
+}
+
+impl RenameRule {
+    pub fn apply(&self, ident: &str) -> String {
+        let words = ident.split('_').filter(|w| !w.is_empty());
+        match self {
+            RenameRule::SnakeCase => ident.to_string(),
+            RenameRule::KebabCase => words.collect::<Vec<_>>().join("-"),
+            RenameRule::PascalCase => words.map(capitalize).collect(),
+            RenameRule::CamelCase => words
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.to_string() } else { capitalize(w) })
+                .collect(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 pub enum Derive {
@@ -366,6 +1260,39 @@ fn get_lit_byte_str<'a>(expr: &syn::Expr) -> Result<&syn::LitByteStr, ()> {
     Err(())
 }
 
+fn parse_root(value: &syn::Expr) -> syn::Expr {
+    match value {
+        syn::Expr::Path(_) => value.clone(),
+        _ if get_lit_byte_str(value).is_ok() => value.clone(),
+        _ => {
+            let path = parse_lit_into_expr_path(value)
+                .expect("`root` should be a byte string or a path to a constant");
+            syn::Expr::Path(path)
+        }
+    }
+}
+
+fn get_lit_int(expr: &syn::Expr) -> Result<&syn::LitInt, ()> {
+    if let syn::Expr::Lit(lit) = expr {
+        if let syn::Lit::Int(l) = &lit.lit {
+            return Ok(l);
+        }
+    }
+    Err(())
+}
+
+/// Parse the value of `vec_size` or `capacity`, which is an integer or a string of an expression.
+fn parse_size_lit(value: syn::Expr) -> syn::Lit {
+    if let syn::Expr::Lit(lit) = value {
+        match lit.lit {
+            syn::Lit::Str(_) | syn::Lit::Int(_) => lit.lit,
+            _ => panic!(),
+        }
+    } else {
+        panic!()
+    }
+}
+
 fn get_lit_str<'a>(lit: &syn::Expr) -> Result<&syn::LitStr, ()> {
     if let syn::Expr::Lit(lit) = lit {
         if let syn::Lit::Str(l) = &lit.lit {
@@ -491,4 +1418,15 @@ impl<'a> Generic<'a> {
             _ => None,
         }
     }
+
+    /// The element type `T` of an `Option<Vec<T>>`.
+    pub fn get_opt_vec(&self) -> Option<&syn::Type> {
+        match self {
+            Generic::Opt(v) => match get_generics(v) {
+                Generic::Vec(t) => Some(t),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
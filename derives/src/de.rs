@@ -1,6 +1,8 @@
 use syn::DeriveInput;
 
-use crate::container::{self, Container, EleType, FieldsSummary, Generic, StructField};
+use crate::container::{
+    self, Container, EleType, FieldsSummary, Generic, MissingPolicy, StructField, UnknownPolicy,
+};
 
 pub fn get_de_impl_block(input: DeriveInput) -> proc_macro2::TokenStream {
     let container = Container::from_ast(&input, container::Derive::Deserialize);
@@ -13,26 +15,40 @@ pub fn get_de_impl_block(input: DeriveInput) -> proc_macro2::TokenStream {
 }
 
 pub fn get_de_enum_impl_block(container: Container) -> proc_macro2::TokenStream {
+    let inline = container.inline_attr();
     macro_rules! children_branches {
         ($attrs:expr, $b:expr) => {
             container.enum_variants.iter().map(|v| {
-                if matches!(&v.ele_type, EleType::Text) {
+                if matches!(&v.ele_type, EleType::Text | EleType::Any) {
                     return quote! {};
                 }
                 let name = v.name.as_ref().expect("should have name");
                 let ty = v.ty;
                 let ident = v.ident;
-                if let Some(ty) = ty {
+                if v.is_transparent() {
+                    let ty = ty.expect("transparent variant should have a type");
+                    quote! {
+                        __t if <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&__t) => {
+                            let __tag = <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags()
+                                .into_iter()
+                                .find(|t| *t == __t)
+                                .unwrap();
+                            let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(__tag, reader, $attrs, $b)?;
+                            let _r = <#ty as ::xmlserde::XmlDeserialize>::__deserialize_from_unparsed_array(vec![(__tag, __u)])?;
+                            return Ok(Self::#ident(_r));
+                        }
+                    }
+                } else if let Some(ty) = ty {
                     quote! {
                         #name => {
-                            let _r = #ty::deserialize(#name, reader, $attrs, $b);
-                            return Self::#ident(_r);
+                            let _r = <#ty as ::xmlserde::XmlDeserialize>::deserialize(#name, reader, $attrs, $b)?;
+                            return Ok(Self::#ident(_r));
                         }
                     }
                 } else {
                     quote! {
                         #name => {
-                            return Self::#ident;
+                            return Ok(Self::#ident);
                         }
                     }
                 }
@@ -56,9 +72,17 @@ pub fn get_de_enum_impl_block(container: Container) -> proc_macro2::TokenStream
 
     let text_function = if let Some(text_ty) = text_opt {
         let ident = text_ident.expect("should have ident for text");
+        let field = ident.to_string();
         quote! {
-            fn __deserialize_from_text(s: &str) -> Option<Self> {
-                Some(Self::#ident(<#text_ty as ::xmlserde::XmlValue>::deserialize(s).unwrap()))
+            fn __deserialize_from_text(s: &str) -> Option<Result<Self, ::xmlserde::XmlDeError>> {
+                let r = <#text_ty as ::xmlserde::XmlValue>::deserialize(s)
+                    .map(Self::#ident)
+                    .map_err(|__e| ::xmlserde::XmlDeError::TextParse {
+                        field: String::from(#field),
+                        value: String::from(s),
+                        reason: __e,
+                    });
+                Some(r)
             }
         }
     } else {
@@ -71,51 +95,156 @@ pub fn get_de_enum_impl_block(container: Container) -> proc_macro2::TokenStream
     let children_tags = container
         .enum_variants
         .iter()
-        .filter(|v| matches!(v.ele_type, EleType::Child))
+        .filter(|v| matches!(v.ele_type, EleType::Child) && !v.is_transparent())
         .map(|v| {
             let name = v.name.as_ref().expect("should have `name` for `child`");
             quote! {#name}
         });
+    let transparent_children_tags = container
+        .enum_variants
+        .iter()
+        .filter(|v| v.is_transparent())
+        .map(|v| {
+            let ty = v.ty.expect("transparent variant should have a type");
+            quote! {<#ty as ::xmlserde::XmlDeserialize>::__get_children_tags()}
+        });
     let exact_tags = children_branches!(attrs, is_empty);
+    let any_ident = container
+        .enum_variants
+        .iter()
+        .find(|v| matches!(v.ele_type, EleType::Any))
+        .map(|v| v.ident);
+    let encounter_unknown = |is_empty: bool| match container.on_unknown {
+        UnknownPolicy::Skip => quote! {},
+        UnknownPolicy::Error => quote! {
+            let _field = String::from_utf8_lossy(_s.name().into_inner()).into_owned();
+            return Err(::xmlserde::XmlDeError::UnknownField { field: _field })
+        },
+        UnknownPolicy::Capture => {
+            let ident = any_ident.expect("should have an `any` variant");
+            quote! {
+                let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(_s.name().into_inner(), reader, _s.attributes(), #is_empty)?;
+                return Ok(Self::#ident(__u));
+            }
+        }
+    };
+    let (start_unknown, empty_unknown) = (encounter_unknown(false), encounter_unknown(true));
+    let deny_unknown_children = match container.on_unknown {
+        UnknownPolicy::Skip => quote! {},
+        UnknownPolicy::Error => quote! {
+            fn __deny_unknown_children() -> bool {
+                true
+            }
+        },
+        UnknownPolicy::Capture => {
+            let ident = any_ident.expect("should have an `any` variant");
+            quote! {
+                fn __capture_unknown_children() -> bool {
+                    true
+                }
+
+                fn __from_unknown_child(u: ::xmlserde::Unparsed) -> Option<Self> {
+                    Some(Self::#ident(u))
+                }
+            }
+        }
+    };
+    let (missing_result, on_missing) = match container.on_missing {
+        MissingPolicy::Error => {
+            let ty = container.original.ident.to_string();
+            (
+                quote! {Err(::xmlserde::XmlDeError::MissingVariant { ty: String::from(#ty) })},
+                quote! {},
+            )
+        }
+        MissingPolicy::Default => (
+            quote! {Ok(Self::default())},
+            quote! {
+                fn __on_missing() -> Option<Self> {
+                    Some(Self::default())
+                }
+            },
+        ),
+    };
+    // The variant selected by the attribute takes precedence over the element name.
+    let select_by_attr = match &container.type_attr {
+        Some(type_attr) => {
+            let branches = container.enum_variants.iter().filter_map(|v| {
+                let value = v.type_value.as_ref()?;
+                let ty = v.ty.expect("should have a type");
+                let ident = v.ident;
+                Some(quote! {
+                    #value => {
+                        let _r = <#ty as ::xmlserde::XmlDeserialize>::deserialize(tag, reader, attrs, is_empty)?;
+                        return Ok(Self::#ident(_r));
+                    }
+                })
+            });
+            quote! {
+                let __type = attrs
+                    .clone()
+                    .flatten()
+                    .find(|__a| __a.key.into_inner() == #type_attr)
+                    .map(|__a| ::xmlserde::__attr_value(&__a));
+                if let Some(__type) = __type {
+                    match __type.as_bytes() {
+                        #(#branches)*
+                        _ => {},
+                    }
+                }
+            }
+        }
+        None => quote! {},
+    };
     quote! {
         #[allow(unused_assignments)]
         impl #impl_generics ::xmlserde::XmlDeserialize for #ident #type_generics #where_clause {
+            #inline
             fn deserialize<B: std::io::BufRead>(
                 tag: &[u8],
                 reader: &mut ::xmlserde::quick_xml::Reader<B>,
                 attrs: ::xmlserde::quick_xml::events::attributes::Attributes,
                 is_empty: bool,
-            ) -> Self {
+            ) -> Result<Self, ::xmlserde::XmlDeError> {
                 use ::xmlserde::quick_xml::events::*;
+                #select_by_attr
                 match tag {
                     #(#exact_tags)*
                     _ => {},
                 }
-                let mut buf = Vec::<u8>::new();
-                let mut result = Option::<Self>::None;
-                loop {
-                    match reader.read_event_into(&mut buf) {
-                        Ok(Event::End(e)) if e.name().into_inner() == tag => {
-                            break
-                        },
-                        Ok(Event::Start(_s)) => match _s.name().into_inner() {
-                            #(#event_start_branches)*
-                            _ => {},
-                        },
-                        Ok(Event::Empty(_s)) => match _s.name().into_inner() {
-                            #(#event_empty_branches)*
+                let mut buf = ::xmlserde::__take_buf();
+                if !is_empty {
+                    loop {
+                        buf.clear();
+                        match reader.read_event_into(&mut buf) {
+                            Ok(Event::End(e)) if e.name().into_inner() == tag => {
+                                break
+                            },
+                            Ok(Event::Start(_s)) => match _s.name().into_inner() {
+                                #(#event_start_branches)*
+                                _ => {
+                                    #start_unknown
+                                },
+                            },
+                            Ok(Event::Empty(_s)) => match _s.name().into_inner() {
+                                #(#event_empty_branches)*
+                                _ => {
+                                    #empty_unknown
+                                },
+                            }
+                            Ok(Event::Eof) => break,
+                            Err(__e) => return Err(__e.into()),
                             _ => {},
                         }
-                        Ok(Event::Eof) => break,
-                        Err(_) => break,
-                        _ => {},
                     }
                 }
-                result.expect("did not find any tag")
+                #missing_result
             }
 
             fn __get_children_tags() -> Vec<&'static [u8]> {
-                vec![#(#children_tags,)*]
+                let mut r: Vec<&'static [u8]> = vec![#(#children_tags,)*];
+                #(r.extend(#transparent_children_tags.into_iter());)*
+                r
             }
 
             #text_function
@@ -123,12 +252,36 @@ pub fn get_de_enum_impl_block(container: Container) -> proc_macro2::TokenStream
             fn __is_enum() -> bool {
                 true
             }
+
+            #deny_unknown_children
+
+            #on_missing
         }
     }
 }
 
 pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStream {
+    let inline = container.inline_attr();
     let result = get_result(&container.struct_fields);
+    // The bindings of an element are only recorded if the element resolves a prefix itself
+    // or has children which may do that.
+    let push_ns_scope = if container.struct_fields.iter().any(|f| {
+        f.ns.is_some()
+            || matches!(
+                f.ty,
+                EleType::Child
+                    | EleType::Untag
+                    | EleType::UntaggedEnum
+                    | EleType::UntaggedStruct
+                    | EleType::Flatten
+                    | EleType::Any
+                    | EleType::TagNs
+            )
+    }) {
+        quote! {let __ns_scope = ::xmlserde::__push_ns_scope(attrs.clone());}
+    } else {
+        quote! {}
+    };
     let summary = FieldsSummary::from_fields(container.struct_fields);
     let fields_init = get_fields_init(&summary);
     let result_untagged_structs = get_untagged_struct_fields_result(&summary.untagged_structs);
@@ -139,11 +292,69 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
         self_closed_children,
         untagged_enums,
         untagged_structs,
+        tag_ns,
+        tag_name,
+        rest_attrs,
+        attr_map,
+        any,
+        attr_order,
+        flattens,
     } = summary;
-    let get_children_tags = if children.len() > 0 || untagged_enums.len() > 0 {
-        let names = children.iter().map(|f| {
+    // Bound to the hidden names so that a field named like `tag` does not shadow the parameters.
+    let tag_ns_init = match &tag_ns {
+        Some(f) if f.generic.is_opt() => quote! {let __tag_ns = ::xmlserde::__element_ns(tag);},
+        Some(_) => quote! {let __tag_ns = ::xmlserde::__element_ns(tag).unwrap_or_default();},
+        None => quote! {},
+    };
+    let tag_name_init = match &tag_name {
+        Some(f) if f.generic.is_opt() => {
+            quote! {let __tag_name = Some(String::from_utf8_lossy(tag).into_owned());}
+        }
+        Some(_) => quote! {let __tag_name = String::from_utf8_lossy(tag).into_owned();},
+        None => quote! {},
+    };
+    // `trim` is skipped in the elements with `xml:space="preserve"` on themselves or their ancestors.
+    let preserve_space_init =
+        if text.as_ref().is_some_and(|t| t.trim) || untagged_enums.iter().any(|f| f.trim) {
+            quote! {let __preserve_space = ::xmlserde::__preserve_space(attrs.clone());}
+        } else {
+            quote! {}
+        };
+    let flatten_tys = flattens.iter().map(|f| &f.original.ty).collect::<Vec<_>>();
+    let flatten_vars = flattens.iter().map(flatten_ident).collect::<Vec<_>>();
+    let flatten_tags = flattens.iter().map(flatten_tags_ident).collect::<Vec<_>>();
+    let flatten_init = quote! {
+        #(let mut #flatten_vars = ::xmlserde::__unparsed_from_attrs(attrs.clone());)*
+        #(let #flatten_tags = <#flatten_tys as ::xmlserde::XmlDeserialize>::__get_children_tags();)*
+    };
+    let get_attr_names = if attrs.len() > 0 || flattens.len() > 0 {
+        let names = attrs.iter().flat_map(|f| {
             let n = f.name.as_ref().expect("should have name");
-            quote! {#n}
+            std::iter::once(n).chain(f.aliases.iter())
+        });
+        quote! {
+            fn __get_attr_names() -> Vec<&'static [u8]> {
+                let mut r: Vec<&'static [u8]> = vec![#(#names,)*];
+                #(r.extend(<#flatten_tys as ::xmlserde::XmlDeserialize>::__get_attr_names());)*
+                r
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let any_init = match any.as_ref().map(|f| &f.capacity) {
+        Some(Some(lit)) => {
+            let size = size_expr(lit);
+            quote! {let mut __any = Vec::<::xmlserde::Unparsed>::with_capacity(#size);}
+        }
+        Some(None) => quote! {let mut __any = Vec::<::xmlserde::Unparsed>::new();},
+        None => quote! {},
+    };
+    let get_children_tags = if children.len() > 0 || untagged_enums.len() > 0 || flattens.len() > 0
+    {
+        let names = children.iter().flat_map(|f| {
+            let n = f.name.as_ref().expect("should have name");
+            std::iter::once(n).chain(f.aliases.iter())
         });
         let untagged_enums = untagged_enums.iter().map(|f| {
             let ty = match &f.generic {
@@ -151,12 +362,13 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
                 Generic::Opt(t) => t,
                 Generic::None => &f.original.ty,
             };
-            quote! {#ty::__get_children_tags()}
+            quote! {<#ty as ::xmlserde::XmlDeserialize>::__get_children_tags()}
         });
         quote! {
             fn __get_children_tags() -> Vec<&'static [u8]> {
                 let mut r: Vec<&'static [u8]> = vec![#(#names,)*];
                 #(r.extend(#untagged_enums.into_iter());)*
+                #(r.extend(<#flatten_tys as ::xmlserde::XmlDeserialize>::__get_children_tags());)*
                 r
             }
         }
@@ -166,17 +378,49 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
     let attr_len = attrs.len();
     let sfc_len = self_closed_children.len();
     let vec_init = get_vec_init(&children);
-    let attr_branches = attrs.into_iter().map(|a| attr_match_branch(a));
-    let child_branches = children_match_branch(&children, &untagged_enums, &untagged_structs);
-    let sfc_branch = sfc_match_branch(self_closed_children);
+    let ci = container.case_insensitive_names;
+    let attr_branches = attrs.into_iter().map(|a| attr_match_branch(a, ci));
+    let deny_duplicate = container.deny_duplicate;
+    let seen_init = if deny_duplicate {
+        let seen = children
+            .iter()
+            .filter(|f| !f.generic.is_vec())
+            .map(|f| seen_ident(f));
+        quote! {#(let mut #seen = false;)*}
+    } else {
+        quote! {}
+    };
+    let read_attr = if deny_duplicate {
+        quote! {
+            let attr = attr.map_err(::xmlserde::quick_xml::Error::from)?;
+        }
+    } else {
+        quote! {
+            let attr = match attr {
+                Ok(attr) => attr,
+                Err(_) => continue,
+            };
+        }
+    };
+    let child_branches = children_match_branch(
+        &children,
+        &untagged_enums,
+        &untagged_structs,
+        &flattens,
+        any.is_some(),
+        ci,
+        deny_duplicate,
+    );
+    let sfc_branch = sfc_match_branch(self_closed_children, ci);
     let ident = &container.original.ident;
     let (impl_generics, type_generics, where_clause) = container.original.generics.split_for_impl();
-    let text_branch = {
-        if let Some(t) = text {
-            Some(text_match_branch(t))
-        } else {
-            None
+    let text_empty = text.as_ref().map(text_empty_fallback);
+    let (text_branch, text_end) = match text {
+        Some(t) => {
+            let (branch, end) = text_match_branch(t);
+            (Some(branch), Some(end))
         }
+        None => (None, None),
     };
     let get_root = if let Some(r) = &container.root {
         quote! {
@@ -187,22 +431,106 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
     } else {
         quote! {}
     };
+    let get_case_insensitive = if ci {
+        quote! {
+            fn __case_insensitive_names() -> bool {
+                true
+            }
+        }
+    } else {
+        quote! {}
+    };
 
     // Only those structs with only children can be untagged
-    let deserialize_from_unparsed =
-        if children.len() > 0 && attr_len == 0 && sfc_len == 0 && untagged_enums.len() == 0 {
-            get_deserialize_from_unparsed(&children)
-        } else {
-            quote! {}
-        };
+    let deserialize_from_unparsed = if children.len() > 0
+        && children.iter().all(|c| c.from_attr.is_none())
+        && attr_len == 0
+        && sfc_len == 0
+        && untagged_enums.len() == 0
+        && tag_ns.is_none()
+        && tag_name.is_none()
+        && rest_attrs.is_none()
+        && attr_map.is_none()
+        && any.is_none()
+        && attr_order.is_none()
+        && flattens.is_empty()
+    {
+        get_deserialize_from_unparsed(&children)
+    } else {
+        quote! {}
+    };
     let encounter_unknown = if container.deny_unknown {
         quote! {
-            let _field = std::str::from_utf8(_field).unwrap();
-            panic!("encoutnering unknown field: {:#?}", _field)
+            let _field = String::from_utf8_lossy(_field).into_owned();
+            return Err(::xmlserde::XmlDeError::UnknownField { field: _field })
         }
     } else {
         quote! {}
     };
+    // The namespaces declared by the container are written again when serializing.
+    let mut declared = container
+        .custom_ns
+        .iter()
+        .map(|(prefix, _)| {
+            let mut key = b"xmlns:".to_vec();
+            key.extend(prefix.value());
+            syn::LitByteStr::new(&key, prefix.span())
+        })
+        .collect::<Vec<_>>();
+    if let Some(ns) = &container.with_ns {
+        declared.push(syn::LitByteStr::new(b"xmlns", ns.span()));
+    }
+    let (rest_attrs_init, encounter_unknown_attr) = match (&rest_attrs, &attr_map) {
+        (Some(_), _) => (
+            quote! {let mut __rest_attrs = String::new();},
+            quote! {
+                let __declared: &[&[u8]] = &[#(#declared),*];
+                ::xmlserde::__push_rest_attr(&mut __rest_attrs, &attr, __declared);
+            },
+        ),
+        (None, Some(f)) => {
+            let ty = &f.original.ty;
+            let init = match &f.capacity {
+                Some(lit) => {
+                    let size = size_expr(lit);
+                    quote! {<#ty>::with_capacity(#size)}
+                }
+                None => quote! {<#ty as ::std::default::Default>::default()},
+            };
+            (
+                quote! {let mut __attr_map = #init;},
+                quote! {
+                    let __declared: &[&[u8]] = &[#(#declared),*];
+                    ::xmlserde::__push_attr_map(&mut __attr_map, &attr, __declared);
+                },
+            )
+        }
+        (None, None) if flattens.is_empty() => (
+            quote! {},
+            quote! {
+                let _field = attr.key.into_inner();
+                #encounter_unknown;
+            },
+        ),
+        (None, None) => (
+            quote! {},
+            quote! {
+                let _field = attr.key.into_inner();
+                // The attributes of the flattened fields are known.
+                if !(false #(|| <#flatten_tys as ::xmlserde::XmlDeserialize>::__get_attr_names().iter().any(|__n| *__n == _field))*) {
+                    #encounter_unknown;
+                }
+            },
+        ),
+    };
+    let (attr_order_init, record_attr_order) = if attr_order.is_some() {
+        (
+            quote! {let mut __attr_order = Vec::<String>::new();},
+            quote! {__attr_order.push(String::from_utf8_lossy(attr.key.into_inner()).into_owned());},
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
     let encounter_unknown_branch = quote! {
         Ok(Event::Empty(_s)) => {
             let _field = _s.name().into_inner();
@@ -216,29 +544,39 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
     quote! {
         #[allow(unused_assignments)]
         impl #impl_generics ::xmlserde::XmlDeserialize for #ident #type_generics #where_clause {
+            #inline
             fn deserialize<B: std::io::BufRead>(
                 tag: &[u8],
                 reader: &mut ::xmlserde::quick_xml::Reader<B>,
                 attrs: ::xmlserde::quick_xml::events::attributes::Attributes,
                 is_empty: bool,
-            ) -> Self {
+            ) -> Result<Self, ::xmlserde::XmlDeError> {
                 #fields_init
-                attrs.into_iter().for_each(|attr| {
-                    if let Ok(attr) = attr {
-                        match attr.key.into_inner() {
-                            #(#attr_branches)*
-                            _ => {
-                                let _field = attr.key.into_inner();
-                                #encounter_unknown;
-                            },
-                        }
+                #push_ns_scope
+                #preserve_space_init
+                #tag_ns_init
+                #tag_name_init
+                #rest_attrs_init
+                #attr_order_init
+                #flatten_init
+                for attr in attrs.into_iter() {
+                    #read_attr
+                    #record_attr_order
+                    match attr.key.into_inner() {
+                        #(#attr_branches)*
+                        _ => {
+                            #encounter_unknown_attr
+                        },
                     }
-                });
-                let mut buf = Vec::<u8>::new();
+                }
+                let mut buf = ::xmlserde::__take_buf();
                 use ::xmlserde::quick_xml::events::Event;
                 #vec_init
+                #any_init
+                #seen_init
                 if is_empty {} else {
                     loop {
+                        buf.clear();
                         match reader.read_event_into(&mut buf) {
                             Ok(Event::End(e)) if e.name().into_inner() == tag => {
                                 break
@@ -248,18 +586,22 @@ pub fn get_de_struct_impl_block(container: Container) -> proc_macro2::TokenStrea
                             #text_branch
                             #encounter_unknown_branch
                             Ok(Event::Eof) => break,
-                            Err(_) => break,
+                            Err(__e) => return Err(__e.into()),
                             _ => {},
                         }
                     }
                 }
+                #text_end
+                #text_empty
                 #result_untagged_structs
-                Self {
+                Ok(Self {
                     #result
-                }
+                })
             }
             #get_root
+            #get_case_insensitive
             #get_children_tags
+            #get_attr_names
             #deserialize_from_unparsed
         }
 
@@ -276,12 +618,12 @@ fn get_untagged_struct_fields_result(fileds: &[StructField]) -> proc_macro2::Tok
             Generic::Vec(_) => unreachable!(),
             Generic::Opt(_t) => quote! {
                 if #ident_opt_unparsed_array.len() > 0 {
-                    #ident = Some(#_t::__deserialize_from_unparsed_array(#ident_opt_unparsed_array));
+                    #ident = Some(<#_t as ::xmlserde::XmlDeserialize>::__deserialize_from_unparsed_array(#ident_opt_unparsed_array)?);
                 }
             },
             Generic::None => quote! {
                 if #ident_unparsed_array.len() > 0 {
-                    #ident = Some(#ty::__deserialize_from_unparsed_array(#ident_unparsed_array));
+                    #ident = Some(<#ty as ::xmlserde::XmlDeserialize>::__deserialize_from_unparsed_array(#ident_unparsed_array)?);
                 }
             },
         }
@@ -293,9 +635,55 @@ fn get_untagged_struct_fields_result(fileds: &[StructField]) -> proc_macro2::Tok
 fn get_result(fields: &[StructField]) -> proc_macro2::TokenStream {
     let branch = fields.iter().map(|f| {
         let ident = f.original.ident.as_ref().unwrap();
-        if f.is_required() {
+        let is_untagged_enum = matches!(f.ty, EleType::Untag | EleType::UntaggedEnum);
+        if matches!(f.ty, EleType::TagNs) {
+            quote! {
+                #ident: __tag_ns,
+            }
+        } else if matches!(f.ty, EleType::TagName) {
+            quote! {
+                #ident: __tag_name,
+            }
+        } else if matches!(f.ty, EleType::RestAttrsJoined) {
+            quote! {
+                #ident: __rest_attrs,
+            }
+        } else if matches!(f.ty, EleType::AttrOrder) {
+            quote! {
+                #ident: __attr_order,
+            }
+        } else if matches!(f.ty, EleType::Any) {
+            quote! {
+                #ident: __any,
+            }
+        } else if matches!(f.ty, EleType::AttrMap) {
+            quote! {
+                #ident: __attr_map,
+            }
+        } else if matches!(f.ty, EleType::Flatten) {
+            let var = flatten_ident(f);
+            let ty = &f.original.ty;
+            quote! {
+                #ident: #var.deserialize_to::<#ty>()?,
+            }
+        } else if is_untagged_enum && f.is_required() && f.default.is_some() {
+            let path = f.default.as_ref().unwrap();
+            quote! {
+                #ident: #ident.unwrap_or_else(#path),
+            }
+        } else if is_untagged_enum && f.is_required() {
+            let ty = &f.original.ty;
+            let missing = missing_field(f);
             quote! {
-                #ident: #ident.unwrap(),
+                #ident: match #ident {
+                    Some(__v) => __v,
+                    None => <#ty as ::xmlserde::XmlDeserialize>::__on_missing().ok_or_else(|| #missing)?,
+                },
+            }
+        } else if f.is_required() {
+            let missing = missing_field(f);
+            quote! {
+                #ident: #ident.ok_or_else(|| #missing)?,
             }
         } else {
             quote! {
@@ -315,7 +703,11 @@ fn get_fields_init(fields: &FieldsSummary) -> proc_macro2::TokenStream {
                 quote! {let mut #ident = #p();}
             }
             None => {
-                if let Some(opt) = f.generic.get_opt() {
+                if let Some(v) = f.generic.get_vec() {
+                    quote! {
+                        let mut #ident = Vec::<#v>::new();
+                    }
+                } else if let Some(opt) = f.generic.get_opt() {
                     quote! {
                         let mut #ident = Option::<#opt>::None;
                     }
@@ -358,10 +750,12 @@ fn get_fields_init(fields: &FieldsSummary) -> proc_macro2::TokenStream {
             // let ty = &f.original.ty;
             match &f.default {
                 Some(e) => quote! {
-                        let mut #ident = #e();
+                    let mut #ident = #e();
+                    let mut __text = Option::<String>::None;
                 },
                 None => quote! {
                     let mut #ident = Option::<#ty>::None;
+                    let mut __text = Option::<String>::None;
                 },
             }
         }
@@ -376,7 +770,9 @@ fn get_fields_init(fields: &FieldsSummary) -> proc_macro2::TokenStream {
     let untagged_enums_init = fields.untagged_enums.iter().map(|f| {
         let ident = f.original.ident.as_ref().unwrap();
 
-        if let Some(path) = &f.default {
+        // A required untag keeps an `Option` so that its default can also
+        // stand in when the element is present but matches no variant.
+        if let (Some(path), false) = (&f.default, matches!(f.generic, Generic::None)) {
             return quote! {let mut #ident = #path();};
         }
 
@@ -446,34 +842,41 @@ fn get_deserialize_from_unparsed(children: &[StructField]) -> proc_macro2::Token
             .name
             .as_ref()
             .expect("types can not have recursive untagged fields");
+        let names = std::iter::once(name).chain(c.aliases.iter());
+        let name = quote! {#(#names)|*};
         let original_type = &c.original.ty;
         let ident = c.original.ident.as_ref().unwrap();
         match &c.generic {
             Generic::Vec(t) => {
                 quote! {
                     #name => {
-                        #ident.push(content.deserialize_to::<#t>().unwrap());
+                        #ident.push(content.deserialize_to::<#t>()?);
                     }
                 }
             }
-            Generic::Opt(t) => {
-                quote! {
+            Generic::Opt(t) => match c.generic.get_opt_vec() {
+                Some(t) => quote! {
                     #name => {
-                        #ident = Some(content.deserialize_to::<#t>().unwrap());
+                        #ident.get_or_insert_with(Vec::new).push(content.deserialize_to::<#t>()?);
                     }
-                }
-            }
+                },
+                None => quote! {
+                    #name => {
+                        #ident = Some(content.deserialize_to::<#t>()?);
+                    }
+                },
+            },
             Generic::None => {
                 if c.default.is_some() {
                     quote! {
                         #name => {
-                            #ident = content.deserialize_to::<#original_type>().unwrap();
+                            #ident = content.deserialize_to::<#original_type>()?;
                         }
                     }
                 } else {
                     quote! {
                         #name => {
-                            #ident = Some(content.deserialize_to::<#original_type>().unwrap());
+                            #ident = Some(content.deserialize_to::<#original_type>()?);
                         }
                     }
                 }
@@ -484,8 +887,9 @@ fn get_deserialize_from_unparsed(children: &[StructField]) -> proc_macro2::Token
         let idents = children.iter().map(|c| {
             let ident = c.original.ident.as_ref().unwrap();
             if c.is_required() {
+                let missing = missing_field(c);
                 quote! {
-                    #ident: #ident.expect("missing field")
+                    #ident: #ident.ok_or_else(|| #missing)?
                 }
             } else {
                 quote! {
@@ -494,20 +898,22 @@ fn get_deserialize_from_unparsed(children: &[StructField]) -> proc_macro2::Token
             }
         });
         quote! {
-            Self {
+            Ok(Self {
                 #(#idents),*
-            }
+            })
         }
     };
     quote! {
-        fn __deserialize_from_unparsed_array(array: Vec<(&'static [u8], ::xmlserde::Unparsed)>) -> Self {
+        fn __deserialize_from_unparsed_array(
+            array: Vec<(&'static [u8], ::xmlserde::Unparsed)>,
+        ) -> Result<Self, ::xmlserde::XmlDeError> {
             #(#init)*
-            array.into_iter().for_each(|(tag, content)| {
+            for (tag, content) in array.into_iter() {
                 match tag {
                     #(#body),*
                     _ => {},
                 }
-            });
+            }
             #result
         }
     }
@@ -521,19 +927,9 @@ fn get_vec_init(children: &[StructField]) -> proc_macro2::TokenStream {
             Some(lit) => {
                 let vec_ty = &c.generic.get_vec().unwrap();
                 let ident = c.original.ident.as_ref().unwrap();
-                match lit {
-                    syn::Lit::Str(s) => {
-                        let path = container::parse_lit_str::<syn::Expr>(s).unwrap();
-                        quote! {
-                            #ident = Vec::<#vec_ty>::with_capacity(#path as usize);
-                        }
-                    }
-                    syn::Lit::Int(i) => {
-                        quote! {
-                            #ident = Vec::<#vec_ty>::with_capacity(#i);
-                        }
-                    }
-                    _ => panic!(""),
+                let size = size_expr(lit);
+                quote! {
+                    #ident = Vec::<#vec_ty>::with_capacity(#size);
                 }
             }
             None => {
@@ -545,49 +941,127 @@ fn get_vec_init(children: &[StructField]) -> proc_macro2::TokenStream {
     }
 }
 
-fn sfc_match_branch(fields: Vec<StructField>) -> proc_macro2::TokenStream {
-    if fields.len() == 0 {
-        return quote! {};
+/// Get the expression of a `vec_size` or a `capacity`.
+fn size_expr(lit: &syn::Lit) -> proc_macro2::TokenStream {
+    match lit {
+        syn::Lit::Str(s) => {
+            let path = container::parse_lit_str::<syn::Expr>(s).unwrap();
+            quote! {#path as usize}
+        }
+        syn::Lit::Int(i) => quote! {#i},
+        _ => panic!(""),
     }
-    let mut idents = vec![];
-    let mut tags = vec![];
-    fields.iter().for_each(|f| {
+}
+
+/// Get the pattern matching the name and the aliases of `field`. When `ci` is true or there are
+/// aliases, the name is bound to `__t`. And `ci` compares the names ignoring ASCII case.
+fn name_pattern(field: &StructField, ci: bool) -> proc_macro2::TokenStream {
+    let tag = field.name.as_ref().expect("should have name");
+    let tags = std::iter::once(tag).chain(field.aliases.iter());
+    if let Some(ns) = &field.ns {
+        return quote! {
+            __t if #(::xmlserde::__element_matches_ns(__t, #tags, #ns, s.attributes(), #ci))||*
+        };
+    }
+    let name = if field.ignore_prefix {
+        quote! {::xmlserde::__local_name(__t)}
+    } else {
+        quote! {__t}
+    };
+    if ci {
+        quote! {__t if #(#name.eq_ignore_ascii_case(#tags))||*}
+    } else if field.aliases.is_empty() && !field.ignore_prefix {
+        quote! {#tag}
+    } else {
+        quote! {__t if #(#name == #tags)||*}
+    }
+}
+
+fn sfc_match_branch(fields: Vec<StructField>, ci: bool) -> proc_macro2::TokenStream {
+    let branches = fields.iter().map(|f| {
         if !matches!(f.ty, EleType::SelfClosedChild) {
             panic!("")
         }
         let tag = f.name.as_ref().unwrap();
-        tags.push(tag);
         let ident = f.original.ident.as_ref().unwrap();
-        idents.push(ident);
+        let name = if f.ignore_prefix {
+            quote! {::xmlserde::__local_name(__s.name().into_inner())}
+        } else {
+            quote! {__s.name().into_inner()}
+        };
+        let cond = if let Some(ns) = &f.ns {
+            quote! {::xmlserde::__element_matches_ns(__s.name().into_inner(), #tag, #ns, __s.attributes(), #ci)}
+        } else if ci {
+            quote! {#name.eq_ignore_ascii_case(#tag)}
+        } else {
+            quote! {#name == #tag}
+        };
+        let set = match &f.val {
+            Some(val) => {
+                let field_name = ident.to_string();
+                let tag_name = String::from_utf8_lossy(&tag.value()).into_owned();
+                quote! {
+                    #ident = true;
+                    for attr in __s.attributes().flatten() {
+                        if attr.key.into_inner() == #val {
+                            let s = ::xmlserde::__attr_value(&attr);
+                            #ident = <bool as ::xmlserde::XmlValue>::deserialize(&s).map_err(|__e| {
+                                ::xmlserde::XmlDeError::AttrParse {
+                                    field: String::from(#field_name),
+                                    tag: String::from(#tag_name),
+                                    value: s.to_string(),
+                                    reason: __e,
+                                }
+                            })?;
+                        }
+                    }
+                }
+            }
+            None => quote! {#ident = true;},
+        };
+        quote! {
+            Ok(Event::Empty(__s)) if #cond => {
+                #set
+            }
+        }
     });
-    quote! {
-        #(Ok(Event::Empty(__s)) if __s.name().into_inner() == #tags => {
-            #idents = true;
-        })*
-    }
+    quote! {#(#branches)*}
 }
 
-fn attr_match_branch(field: StructField) -> proc_macro2::TokenStream {
+fn attr_match_branch(field: StructField, ci: bool) -> proc_macro2::TokenStream {
     if !matches!(field.ty, EleType::Attr) {
         panic!("")
     }
     let t = &field.original.ty;
     let tag = field.name.as_ref().expect("should have a field name");
+    let tag = match &field.ns {
+        Some(ns) => {
+            let tags = std::iter::once(tag).chain(field.aliases.iter());
+            quote! {__t if #(::xmlserde::__attr_matches_ns(__t, #tags, #ns, #ci))||*}
+        }
+        None => name_pattern(&field, ci),
+    };
     let ident = field.original.ident.as_ref().expect("should have ident");
+    let field_name = ident.to_string();
+    let tag_name = String::from_utf8_lossy(&field.name.as_ref().unwrap().value()).into_owned();
     if field.generic.is_opt() {
         let opt_ty = field.generic.get_opt().unwrap();
+        let value = de_value(&field, opt_ty, quote! {&s});
         quote! {
             #tag => {
                 use xmlserde::{XmlValue, XmlDeserialize};
-                let s = String::from_utf8(attr.value.into_iter().map(|c| *c).collect()).unwrap();
-                match #opt_ty::deserialize(&s) {
+                let s = ::xmlserde::__attr_value(&attr);
+                match #value {
                     Ok(__v) => {
                         #ident = Some(__v);
                     },
-                    Err(_) => {
-                        // If we used format! here. It would panic!.
-                        // let err_msg = format!("xml value deserialize error: {:?} to {:?}", s, #t);
-                        panic!("deserialize failed in attr opt")
+                    Err(__e) => {
+                        return Err(::xmlserde::XmlDeError::AttrParse {
+                            field: String::from(#field_name),
+                            tag: String::from(#tag_name),
+                            value: s.into_owned(),
+                            reason: __e,
+                        })
                     },
                 }
             }
@@ -598,18 +1072,22 @@ fn attr_match_branch(field: StructField) -> proc_macro2::TokenStream {
         } else {
             quote! {#ident = __v;}
         };
+        let value = de_value(&field, t, quote! {&__s});
         quote! {
             #tag => {
                 use xmlserde::{XmlValue, XmlDeserialize};
-                let __s = String::from_utf8(attr.value.into_iter().map(|c| *c).collect()).unwrap();
-                match #t::deserialize(&__s) {
+                let __s = ::xmlserde::__attr_value(&attr);
+                match #value {
                     Ok(__v) => {
                         #tt
                     },
-                    Err(_) => {
-                        // If we used format! here. It would panic!.
-                        // let err_msg = format!("xml value deserialize error: {:?} to {:?}", s, #t);
-                        panic!("deserialize failed in attr")
+                    Err(__e) => {
+                        return Err(::xmlserde::XmlDeError::AttrParse {
+                            field: String::from(#field_name),
+                            tag: String::from(#tag_name),
+                            value: __s.into_owned(),
+                            reason: __e,
+                        })
                     },
                 }
             },
@@ -617,7 +1095,71 @@ fn attr_match_branch(field: StructField) -> proc_macro2::TokenStream {
     }
 }
 
-fn text_match_branch(field: StructField) -> proc_macro2::TokenStream {
+/// Get the `MissingField` error of a required field.
+fn missing_field(field: &StructField) -> proc_macro2::TokenStream {
+    let ident = field.original.ident.as_ref().unwrap().to_string();
+    let tag = match &field.name {
+        Some(n) => {
+            let n = String::from_utf8_lossy(&n.value()).into_owned();
+            quote! {Some(String::from(#n))}
+        }
+        None => quote! {None},
+    };
+    quote! {
+        ::xmlserde::XmlDeError::MissingField {
+            field: String::from(#ident),
+            tag: #tag,
+        }
+    }
+}
+
+/// Get the expression that deserializes `s` into the value of `ty`, whose type is `Result<#ty, String>`.
+fn de_value(
+    field: &StructField,
+    ty: &syn::Type,
+    s: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if let Some(f) = &field.deserialize_with {
+        quote! {#f(#s)}
+    } else if let Some(with) = &field.with {
+        quote! {#with::deserialize(#s)}
+    } else if field.encode_base64 {
+        quote! {::xmlserde::__deserialize_base64(#s)}
+    } else if field.bare_bool {
+        let name = field.name.as_ref().unwrap();
+        quote! {::xmlserde::__deserialize_bare_bool(#s, #name)}
+    } else if let Some(radix) = field.radix {
+        quote! {<#ty>::from_str_radix(#s, #radix).map_err(|__e| __e.to_string())}
+    } else if let Some((map_sep, kv_sep)) = &field.map_sep {
+        quote! {::xmlserde::__deserialize_map::<#ty>(#s, #map_sep, #kv_sep)}
+    } else if let Some(sep) = &field.sep {
+        if let Some((elem, len)) = field.get_array() {
+            quote! {::xmlserde::__deserialize_array::<#elem, {#len}>(#s, #sep)}
+        } else if let Some(elems) = field.get_tuple() {
+            let len = elems.len();
+            let vars = (0..len)
+                .map(|i| format_ident!("__item{}", i))
+                .collect::<Vec<_>>();
+            quote! {
+                ::xmlserde::__split_tuple::<#len>(#s, #sep).and_then(|[#(#vars),*]| {
+                    Ok((#(<#elems as ::xmlserde::XmlValue>::deserialize(#vars)?,)*))
+                })
+            }
+        } else {
+            let elem = field
+                .generic
+                .get_vec()
+                .expect("`sep` requires a vector or an array");
+            quote! {::xmlserde::__deserialize_list::<#elem>(#s, #sep)}
+        }
+    } else {
+        quote! {<#ty as ::xmlserde::XmlValue>::deserialize(#s)}
+    }
+}
+
+/// The branches collecting the texts and the CDATA sections into `__text`, and the code parsing
+/// `__text` after the element ends. An element can have many of them, like `a<![CDATA[b]]>c`.
+fn text_match_branch(field: StructField) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     if !matches!(field.ty, EleType::Text) {
         panic!("")
     }
@@ -633,24 +1175,72 @@ fn text_match_branch(field: StructField) -> proc_macro2::TokenStream {
     } else {
         quote! {#ident = __v;}
     };
-    quote! {
-        Ok(Event::Text(__s)) => {
-            use ::xmlserde::{XmlValue, XmlDeserialize};
-            let __r = __s.unescape().unwrap();
-            match #t::deserialize(&__r) {
+    let value = de_value(&field, t, quote! {&__r});
+    let field_name = ident.to_string();
+    let trim = if field.trim {
+        quote! {
+            let __r = ::std::borrow::Cow::Borrowed(if __preserve_space {
+                &*__r
+            } else {
+                ::xmlserde::__trim_text(&__r)
+            });
+        }
+    } else {
+        quote! {}
+    };
+    let parse = quote! {
+            #trim
+            match #value {
                 Ok(__v) => {
                     // #ident = v;
                     #tt
                 },
-                Err(_) => {
-                    panic!("deserialize failed in text element")
+                Err(__e) => {
+                    return Err(::xmlserde::XmlDeError::TextParse {
+                        field: String::from(#field_name),
+                        value: __r.into_owned(),
+                        reason: __e,
+                    })
                 }
             }
+    };
+    let branch = quote! {
+        Ok(Event::Text(__s)) => {
+            let __r = ::xmlserde::__unescape_text(&__s)?;
+            __text.get_or_insert_with(String::new).push_str(&__r);
         },
+        Ok(Event::CData(__s)) => {
+            __text.get_or_insert_with(String::new).push_str(&String::from_utf8_lossy(&__s));
+        },
+    };
+    let end = quote! {
+        if let Some(__r) = __text {
+            use ::xmlserde::{XmlValue, XmlDeserialize};
+            let __r = ::std::borrow::Cow::<str>::Owned(__r);
+            #parse
+        }
+    };
+    (branch, end)
+}
+
+/// An `Option<T>` text field of an element without any text is `Some` if `T` can be
+/// deserialized from the empty string, like `Some("")` for `Option<String>`.
+fn text_empty_fallback(field: &StructField) -> proc_macro2::TokenStream {
+    let ident = field.original.ident.as_ref().expect("should have idnet");
+    match field.generic {
+        Generic::Opt(t) => {
+            let value = de_value(field, t, quote! {""});
+            quote! {
+                if #ident.is_none() {
+                    #ident = #value.ok();
+                }
+            }
+        }
+        _ => quote! {},
     }
 }
 
-fn untag_text_enum_branches(untags: &[StructField]) -> proc_macro2::TokenStream {
+fn untag_text_enum_branches(untags: &[&StructField]) -> proc_macro2::TokenStream {
     if untags.len() == 0 {
         return quote! {};
     }
@@ -659,20 +1249,31 @@ fn untag_text_enum_branches(untags: &[StructField]) -> proc_macro2::TokenStream
     untags.into_iter().for_each(|f| {
         let ident = f.original.ident.as_ref().unwrap();
         let ty = &f.original.ty;
+        let text = if f.trim {
+            quote! {
+                if __preserve_space {
+                    &_str
+                } else {
+                    ::xmlserde::__trim_text(&_str)
+                }
+            }
+        } else {
+            quote! {&_str}
+        };
         let branch = match f.generic {
             Generic::Vec(ty) => quote! {
-                if let Some(t) = #ty::__deserialize_from_text(&_str) {
-                    #ident.push(t);
+                if let Some(t) = <#ty as ::xmlserde::XmlDeserialize>::__deserialize_from_text(#text) {
+                    #ident.push(t?);
                 }
             },
             Generic::Opt(ty) => quote! {
-                if let Some(t) = #ty::__deserialize_from_text(&_str) {
-                    #ident = Some(t);
+                if let Some(t) = <#ty as ::xmlserde::XmlDeserialize>::__deserialize_from_text(#text) {
+                    #ident = Some(t?);
                 }
             },
             Generic::None => quote! {
-                if let Some(t) = #ty::__deserialize_from_text(&_str) {
-                    #ident = Some(t);
+                if let Some(t) = <#ty as ::xmlserde::XmlDeserialize>::__deserialize_from_text(#text) {
+                    #ident = Some(t?);
                 }
             },
         };
@@ -692,18 +1293,18 @@ fn untag_enums_match_branch(fields: &[StructField]) -> proc_macro2::TokenStream
         let ty = &f.original.ty;
         let branch = match f.generic {
             Generic::Vec(ty) => quote! {
-                _ty if #ty::__get_children_tags().contains(&_ty) => {
-                    #ident.push(#ty::deserialize(_ty, reader, s.attributes(), is_empty));
+                _ty if <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&_ty) => {
+                    #ident.push(<#ty as ::xmlserde::XmlDeserialize>::deserialize(_ty, reader, s.attributes(), is_empty)?);
                 }
             },
             Generic::Opt(ty) => quote! {
-                _ty if #ty::__get_children_tags().contains(&_ty) => {
-                    #ident = Some(#ty::deserialize(_ty, reader, s.attributes(), is_empty));
+                _ty if <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&_ty) => {
+                    #ident = Some(<#ty as ::xmlserde::XmlDeserialize>::deserialize(_ty, reader, s.attributes(), is_empty)?);
                 }
             },
             Generic::None => quote! {
-                _t if #ty::__get_children_tags().contains(&_t) => {
-                    #ident = Some(#ty::deserialize(_t, reader, s.attributes(), is_empty));
+                _t if <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&_t) => {
+                    #ident = Some(<#ty as ::xmlserde::XmlDeserialize>::deserialize(_t, reader, s.attributes(), is_empty)?);
                 }
             },
         };
@@ -714,6 +1315,32 @@ fn untag_enums_match_branch(fields: &[StructField]) -> proc_macro2::TokenStream
     }
 }
 
+/// Untagged enums declared with `on_unknown = "error"` reject the children
+/// that none of the fields can accept and those with `on_unknown = "capture"` keep them.
+/// The first field declaring either policy handles the child.
+fn untag_unknown_branch(untags: &[StructField]) -> proc_macro2::TokenStream {
+    let checks = untags.iter().map(|f| {
+        let ident = f.original.ident.as_ref().unwrap();
+        let (ty, assign) = match &f.generic {
+            Generic::Vec(t) => (*t, quote! {#ident.push(__r)}),
+            Generic::Opt(t) => (*t, quote! {#ident = Some(__r)}),
+            Generic::None => (&f.original.ty, quote! {#ident = Some(__r)}),
+        };
+        quote! {
+            if <#ty as ::xmlserde::XmlDeserialize>::__capture_unknown_children() {
+                let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(_t, reader, s.attributes(), is_empty)?;
+                if let Some(__r) = <#ty as ::xmlserde::XmlDeserialize>::__from_unknown_child(__u) {
+                    #assign;
+                }
+            } else if <#ty as ::xmlserde::XmlDeserialize>::__deny_unknown_children() {
+                let _field = String::from_utf8_lossy(_t).into_owned();
+                return Err(::xmlserde::XmlDeError::UnknownField { field: _field })
+            }
+        }
+    });
+    quote! {#(#checks) else *}
+}
+
 fn untag_structs_match_branch(fields: &[StructField]) -> proc_macro2::TokenStream {
     if fields.len() == 0 {
         return quote! {};
@@ -729,18 +1356,18 @@ fn untag_structs_match_branch(fields: &[StructField]) -> proc_macro2::TokenStrea
         let branch = match f.generic {
             Generic::Vec(_) => unreachable!(),
             Generic::Opt(t) => quote! {
-                _t if #t::__get_children_tags().contains(&_t) => {
-                    let _r = ::xmlserde::Unparsed::deserialize(_t, reader, s.attributes(), is_empty);
-                    let _tags = #t::__get_children_tags();
-                    let idx = _tags.binary_search(&_t).unwrap();
+                _t if <#t as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&_t) => {
+                    let _r = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(_t, reader, s.attributes(), is_empty)?;
+                    let _tags = <#t as ::xmlserde::XmlDeserialize>::__get_children_tags();
+                    let idx = _tags.iter().position(|__t| *__t == _t).unwrap();
                     #ident_opt_unparsed_array.push((_tags[idx], _r));
                 }
             },
             Generic::None => quote! {
-                _t if #ty::__get_children_tags().contains(&_t) => {
-                    let _r = ::xmlserde::Unparsed::deserialize(_t, reader, s.attributes(), is_empty);
-                    let _tags = #ty::__get_children_tags();
-                    let idx = _tags.binary_search(&_t).unwrap();
+                _t if <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags().contains(&_t) => {
+                    let _r = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(_t, reader, s.attributes(), is_empty)?;
+                    let _tags = <#ty as ::xmlserde::XmlDeserialize>::__get_children_tags();
+                    let idx = _tags.iter().position(|__t| *__t == _t).unwrap();
                     #ident_unparsed_array.push((_tags[idx], _r));
                 }
             },
@@ -752,12 +1379,93 @@ fn untag_structs_match_branch(fields: &[StructField]) -> proc_macro2::TokenStrea
     }
 }
 
+/// The flag telling whether a non-`Vec` child has been met, used by `deny_duplicate_fields`.
+fn seen_ident(field: &StructField) -> syn::Ident {
+    format_ident!("__seen_{}", field.original.ident.as_ref().unwrap())
+}
+
+/// The `Unparsed` collecting the attributes and the children of a `flatten` field.
+fn flatten_ident(field: &StructField) -> syn::Ident {
+    format_ident!("__flatten_{}", field.original.ident.as_ref().unwrap())
+}
+
+/// The children tags of a `flatten` field, which are got once before reading the children.
+fn flatten_tags_ident(field: &StructField) -> syn::Ident {
+    format_ident!("__flatten_tags_{}", field.original.ident.as_ref().unwrap())
+}
+
+/// The branch reading a `child` with `from_attr` from the attribute of the element.
+/// The content of the element is skipped.
+fn from_attr_branch(
+    f: &StructField,
+    from_attr: &syn::LitByteStr,
+    pat: proc_macro2::TokenStream,
+    tag: proc_macro2::TokenStream,
+    check_duplicate: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let ident = f.original.ident.as_ref().unwrap();
+    let ty = match &f.generic {
+        Generic::Vec(t) | Generic::Opt(t) => t,
+        Generic::None => &f.original.ty,
+    };
+    let value = de_value(f, ty, quote! {&__s});
+    let field_name = ident.to_string();
+    let attr_name = String::from_utf8_lossy(&from_attr.value()).into_owned();
+    let missing = quote! {
+        ::xmlserde::XmlDeError::MissingField {
+            field: String::from(#field_name),
+            tag: Some(String::from(#attr_name)),
+        }
+    };
+    let set = match f.generic {
+        Generic::Vec(_) => quote! {#ident.push(__v.ok_or_else(|| #missing)?);},
+        Generic::Opt(_) => quote! {#ident = __v;},
+        Generic::None if f.is_required() => quote! {#ident = Some(__v.ok_or_else(|| #missing)?);},
+        Generic::None => quote! {#ident = __v.ok_or_else(|| #missing)?;},
+    };
+    quote! {
+        #pat => {
+            #check_duplicate
+            let mut __v = None;
+            for attr in s.attributes().flatten() {
+                if attr.key.into_inner() == #from_attr {
+                    let __s = ::xmlserde::__attr_value(&attr);
+                    match #value {
+                        Ok(__r) => __v = Some(__r),
+                        Err(__e) => {
+                            return Err(::xmlserde::XmlDeError::AttrParse {
+                                field: String::from(#field_name),
+                                tag: String::from(#attr_name),
+                                value: __s.into_owned(),
+                                reason: __e,
+                            })
+                        }
+                    }
+                }
+            }
+            if !is_empty {
+                <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), false)?;
+            }
+            #set
+        }
+    }
+}
+
 fn children_match_branch(
     fields: &[StructField],
     untagged_enums: &[StructField],
     untagged_structs: &[StructField],
+    flattens: &[StructField],
+    has_any: bool,
+    ci: bool,
+    deny_duplicate: bool,
 ) -> proc_macro2::TokenStream {
-    if fields.is_empty() && untagged_enums.is_empty() && untagged_structs.is_empty() {
+    if fields.is_empty()
+        && untagged_enums.is_empty()
+        && untagged_structs.is_empty()
+        && flattens.is_empty()
+        && !has_any
+    {
         return quote! {};
     }
     let mut branches = vec![];
@@ -766,21 +1474,78 @@ fn children_match_branch(
             panic!("")
         }
         let tag = f.name.as_ref().expect("should have name");
+        let pat = name_pattern(f, ci);
+        // Pass the actual name to the child so that it can find its end tag.
+        let tag = if ci || !f.aliases.is_empty() || f.ignore_prefix || f.ns.is_some() {
+            quote! {__t}
+        } else {
+            quote! {#tag}
+        };
         let ident = f.original.ident.as_ref().unwrap();
         let t = &f.original.ty;
+        let opt_vec = f.generic.get_opt_vec();
+        let check_duplicate = if deny_duplicate && !f.generic.is_vec() && opt_vec.is_none() {
+            let seen = seen_ident(f);
+            let name = ident.to_string();
+            quote! {
+                if #seen {
+                    return Err(::xmlserde::XmlDeError::DuplicateField { field: String::from(#name) });
+                }
+                #seen = true;
+            }
+        } else {
+            quote! {}
+        };
+        if let Some(from_attr) = &f.from_attr {
+            branches.push(from_attr_branch(f, from_attr, pat, tag, check_duplicate));
+            return;
+        }
         let branch = match f.generic {
+            Generic::Vec(vec_ty) if f.skip_errors => {
+                quote! {
+                    #pat => {
+                        let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
+                        if let Some(__ele) = ::xmlserde::__try_deserialize_unparsed::<#vec_ty>(__u) {
+                            #ident.push(__ele);
+                        }
+                    }
+                }
+            }
             Generic::Vec(vec_ty) => {
                 quote! {
-                    #tag => {
-                        let __ele = #vec_ty::deserialize(#tag, reader, s.attributes(), is_empty);
+                    #pat => {
+                        let __ele = <#vec_ty as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
                         #ident.push(__ele);
                     }
                 }
             }
+            Generic::Opt(_) if opt_vec.is_some() => {
+                let vec_ty = opt_vec.unwrap();
+                quote! {
+                    #pat => {
+                        let __ele = <#vec_ty as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
+                        #ident.get_or_insert_with(Vec::new).push(__ele);
+                    }
+                }
+            }
+            Generic::Opt(opt_ty) if f.emit_empty_on_none => {
+                quote! {
+                    #pat => {
+                        #check_duplicate
+                        if is_empty && s.attributes().next().is_none() {
+                            #ident = None;
+                        } else {
+                            let __f = <#opt_ty as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
+                            #ident = Some(__f);
+                        }
+                    },
+                }
+            }
             Generic::Opt(opt_ty) => {
                 quote! {
-                    #tag => {
-                        let __f = #opt_ty::deserialize(#tag, reader, s.attributes(), is_empty);
+                    #pat => {
+                        #check_duplicate
+                        let __f = <#opt_ty as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
                         #ident = Some(__f);
                     },
                 }
@@ -796,8 +1561,9 @@ fn children_match_branch(
                     }
                 };
                 quote! {
-                    #tag => {
-                        let __f = #t::deserialize(#tag, reader, s.attributes(), is_empty);
+                    #pat => {
+                        #check_duplicate
+                        let __f = <#t as ::xmlserde::XmlDeserialize>::deserialize(#tag, reader, s.attributes(), is_empty)?;
                         #tt
                     },
                 }
@@ -805,32 +1571,68 @@ fn children_match_branch(
         };
         branches.push(branch);
     });
+    let flatten_branches = flattens.iter().map(|f| {
+        let var = flatten_ident(f);
+        let tags = flatten_tags_ident(f);
+        quote! {
+            __t if #tags.contains(&__t) => {
+                let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(__t, reader, s.attributes(), is_empty)?;
+                ::xmlserde::__push_unparsed_child(&mut #var, __t, __u);
+            }
+        }
+    }).collect::<Vec<_>>();
     let untagged_enums_branches = untag_enums_match_branch(&untagged_enums);
     let untagged_structs_branches = untag_structs_match_branch(&untagged_structs);
-    let untag_text_enum = untag_text_enum_branches(untagged_enums);
+    let (ws_untags, untags): (Vec<_>, Vec<_>) =
+        untagged_enums.iter().partition(|f| f.preserve_whitespace);
+    let untag_text_enum = untag_text_enum_branches(&untags);
+    let untag_ws_text_enum = untag_text_enum_branches(&ws_untags);
+    let untag_unknown = if has_any {
+        quote! {
+            let __u = <::xmlserde::Unparsed as ::xmlserde::XmlDeserialize>::deserialize(_t, reader, s.attributes(), is_empty)?;
+            __any.push(__u);
+        }
+    } else {
+        untag_unknown_branch(untagged_enums)
+    };
 
     quote! {
         Ok(Event::Empty(s)) => {
             let is_empty = true;
             match s.name().into_inner() {
                 #(#branches)*
+                #(#flatten_branches)*
                 #untagged_enums_branches
                 #untagged_structs_branches
-                _ => {},
+                _t => {
+                    #untag_unknown
+                },
             }
         }
         Ok(Event::Start(s)) => {
             let is_empty = false;
             match s.name().into_inner() {
                 #(#branches)*
+                #(#flatten_branches)*
                 #untagged_enums_branches
                 #untagged_structs_branches
-                _ => {},
+                _t => {
+                    #untag_unknown
+                },
             }
         }
         Ok(Event::Text(t)) => {
             use ::xmlserde::{XmlValue, XmlDeserialize};
-            let _str = t.unescape().expect("failed to unescape string");
+            let _str = ::xmlserde::__unescape_text(&t)?;
+            #untag_ws_text_enum
+            if _str.trim() != "" {
+                #untag_text_enum
+            }
+        }
+        Ok(Event::CData(t)) => {
+            use ::xmlserde::{XmlValue, XmlDeserialize};
+            let _str = String::from_utf8_lossy(&t);
+            #untag_ws_text_enum
             if _str.trim() != "" {
                 #untag_text_enum
             }
@@ -14,6 +14,7 @@ pub fn get_ser_impl_block(input: DeriveInput) -> proc_macro2::TokenStream {
 
 fn get_ser_enum_impl_block(container: Container) -> proc_macro2::TokenStream {
     let ident = &container.original.ident;
+    let inline = container.inline_attr();
     let (impl_generics, type_generics, where_clause) = container.original.generics.split_for_impl();
     let branches = container.enum_variants.iter().map(|v| {
         let f = v.ident;
@@ -40,6 +41,18 @@ fn get_ser_enum_impl_block(container: Container) -> proc_macro2::TokenStream {
                         let _ = writer.write_event(Event::Text(BytesText::new(&c.serialize())));
                     }
                 }
+            } else if matches!(ele_ty, EleType::Any) {
+                quote! {
+                    Self::#f(c) => {
+                        if tag == b"" {
+                            c.serialize(c.tag().as_bytes(), writer);
+                        } else {
+                            let _ = writer.write_event(Event::Start(BytesStart::new(String::from_utf8_lossy(tag))));
+                            c.serialize(c.tag().as_bytes(), writer);
+                            let _ = writer.write_event(Event::End(BytesEnd::new(String::from_utf8_lossy(tag))));
+                        }
+                    },
+                }
             } else {
                 let name = v.name.as_ref().expect("should have hame");
                 quote! {
@@ -59,6 +72,7 @@ fn get_ser_enum_impl_block(container: Container) -> proc_macro2::TokenStream {
     quote! {
         #[allow(unused_must_use)]
         impl #impl_generics ::xmlserde::XmlSerialize for #ident #type_generics #where_clause {
+            #inline
             fn serialize<W: std::io::Write>(
                 &self,
                 tag: &[u8],
@@ -74,20 +88,21 @@ fn get_ser_enum_impl_block(container: Container) -> proc_macro2::TokenStream {
 }
 
 fn get_ser_struct_impl_block(container: Container) -> proc_macro2::TokenStream {
+    let inline = container.inline_attr();
     let write_ns = match container.with_ns {
         Some(ns) => quote! {
-            attrs.push(Attribute::from((b"xmlns".as_ref(), #ns.as_ref())));
+            start.push_attribute(Attribute::from((b"xmlns".as_ref(), #ns.as_ref())));
         },
         None => quote! {},
     };
     let write_custom_ns = if container.custom_ns.len() == 0 {
         quote! {}
     } else {
-        let cns = container.custom_ns.into_iter().map(|(ns, value)| {
+        let cns = container.custom_ns.iter().map(|(ns, value)| {
             quote! {
                 let mut __vec = b"xmlns:".to_vec();
                 __vec.extend(#ns.to_vec());
-                attrs.push(Attribute::from((__vec.as_ref(), #value.as_ref())));
+                start.push_attribute(Attribute::from((__vec.as_ref(), #value.as_ref())));
             }
         });
         quote! {#(#cns)*}
@@ -99,106 +114,261 @@ fn get_ser_struct_impl_block(container: Container) -> proc_macro2::TokenStream {
         self_closed_children,
         untagged_enums: untags,
         untagged_structs: _,
+        tag_ns: _,
+        tag_name: _,
+        rest_attrs,
+        attr_map,
+        any,
+        attr_order,
+        flattens,
     } = FieldsSummary::from_fields(container.struct_fields);
     if text.is_some() && (children.len() > 0 || self_closed_children.len() > 0 || untags.len() > 0)
     {
         panic!("Cannot have the text and children at the same time.")
     }
-    let init = init_is_empty(&children, &self_closed_children, &untags, &text);
-    let build_attr_and_push = attrs.into_iter().map(|attr| {
-        let name = attr.name.as_ref().unwrap();
-        let ident = attr.original.ident.as_ref().unwrap();
-        match &attr.generic {
-            Generic::Vec(_) => panic!("cannot use a vector in attribute"),
-            Generic::Opt(_) => {
-                quote! {
-                    let mut sr: String;
-                    match &self.#ident {
-                        Some(v) => {
-                            sr = v.serialize();
-                            attrs.push(Attribute::from((#name.as_ref(), sr.as_bytes())));
-                        },
-                        None => {},
+    let selectable = container.selectable;
+    let init = init_is_empty(&children, &self_closed_children, &untags, &text, selectable);
+    let custom_ns = &container.custom_ns;
+    let build_attr_and_push = attrs
+        .into_iter()
+        .map(|attr| {
+            let name = prefixed_name(&attr, custom_ns);
+            let ident = attr.original.ident.as_ref().unwrap();
+            let push = match &attr.generic {
+                Generic::Vec(_) => {
+                    if attr.sep.is_none() {
+                        panic!("cannot use a vector in attribute without `sep`")
+                    }
+                    let value = ser_value_into(&attr, quote! {&self.#ident});
+                    quote! {
+                        if !self.#ident.is_empty() {
+                            __buf.clear();
+                            #value;
+                            start.push_attribute(::xmlserde::__escaped_attr(#name, &__buf));
+                        }
                     }
                 }
-            }
-            Generic::None => match &attr.default {
-                Some(path) => quote! {
-                    let mut ser;
-                    if #path() != self.#ident {
-                        ser = self.#ident.serialize();
-                        attrs.push(Attribute::from((#name.as_ref(), ser.as_bytes())));
+                Generic::Opt(_) => {
+                    let value = ser_value_into(&attr, quote! {v});
+                    quote! {
+                        match &self.#ident {
+                            Some(v) => {
+                                __buf.clear();
+                                #value;
+                                start.push_attribute(::xmlserde::__escaped_attr(#name, &__buf));
+                            },
+                            None => {},
+                        }
+                    }
+                }
+                Generic::None if attr.bare_bool => quote! {
+                    if self.#ident {
+                        ::xmlserde::__push_bare_attr(&mut start, #name);
                     }
                 },
-                None => quote! {
-                    let ser = self.#ident.serialize();
-                    attrs.push(Attribute::from((#name.as_ref(), ser.as_bytes())));
-                },
-            },
+                Generic::None => {
+                    let value = ser_value_into(&attr, quote! {&self.#ident});
+                    match &attr.default {
+                        Some(path) => quote! {
+                            if #path() != self.#ident {
+                                __buf.clear();
+                                #value;
+                                start.push_attribute(::xmlserde::__escaped_attr(#name, &__buf));
+                            }
+                        },
+                        None => quote! {
+                            __buf.clear();
+                            #value;
+                            start.push_attribute(::xmlserde::__escaped_attr(#name, &__buf));
+                        },
+                    }
+                }
+            };
+            if_selected(selectable, ident, unless_skipped(&attr, push))
+        })
+        .collect::<Vec<_>>();
+    let push_rest_attrs = rest_attrs.map(|f| {
+        let ident = f.original.ident.as_ref().unwrap();
+        let push = quote! {
+            ::xmlserde::__push_rest_attrs(&mut start, &self.#ident);
+        };
+        if_selected(selectable, ident, push)
+    });
+    let reorder_attrs = attr_order.map(|f| {
+        let ident = f.original.ident.as_ref().unwrap();
+        quote! {
+            ::xmlserde::__reorder_attrs(&mut start, &self.#ident);
+        }
+    });
+    let flatten_idents = flattens
+        .iter()
+        .map(|f| f.original.ident.as_ref().unwrap())
+        .collect::<Vec<_>>();
+    let push_flatten_attrs = quote! {
+        #(::xmlserde::XmlSerialize::__serialize_flatten_attrs(&self.#flatten_idents, &mut start);)*
+    };
+    let flatten_is_empty = if flattens.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            let is_empty = is_empty #(&& !::xmlserde::XmlSerialize::__has_flatten_children(&self.#flatten_idents))*;
         }
+    };
+    let (write_any, any_is_empty) = match &any {
+        Some(f) => {
+            let ident = f.original.ident.as_ref().unwrap();
+            let write = if_selected(
+                selectable,
+                ident,
+                quote! {::xmlserde::__write_unparsed_any(&self.#ident, writer);},
+            );
+            let cond = selected_and(selectable, ident, quote! {!self.#ident.is_empty()});
+            (write, quote! {let is_empty = is_empty && !(#cond);})
+        }
+        None => (quote! {}, quote! {}),
+    };
+    let push_attr_map = attr_map.map(|f| {
+        let ident = f.original.ident.as_ref().unwrap();
+        let push = quote! {
+            ::xmlserde::__push_attr_map_attrs(&mut start, &self.#ident);
+        };
+        if_selected(selectable, ident, push)
     });
     let write_text_or_children = if let Some(t) = text {
         let ident = t.original.ident.as_ref().unwrap();
-        if t.generic.is_opt() {
+        let write_text = if t.generic.is_opt() {
+            let value = ser_value_into(&t, quote! {__d});
             quote! {
                 match &self.#ident {
                     None => {},
                     Some(__d) => {
-                        let r = __d.serialize();
-                        let event = BytesText::new(&r);
+                        __buf.clear();
+                        #value;
+                        let event = BytesText::new(&__buf);
                         writer.write_event(Event::Text(event));
                     }
                 }
             }
         } else {
+            let value = ser_value_into(&t, quote! {&self.#ident});
             quote! {
-                let r = self.#ident.serialize();
-                let event = BytesText::new(&r);
+                __buf.clear();
+                #value;
+                let event = BytesText::new(&__buf);
                 writer.write_event(Event::Text(event));
             }
-        }
+        };
+        if_selected(selectable, ident, unless_skipped(&t, write_text))
     } else {
         let write_scf = self_closed_children.into_iter().map(|f| {
             let ident = f.original.ident.as_ref().unwrap();
-            let name = f.name.as_ref().expect("should have name");
-            quote! {
-                if self.#ident {
-                    let event = BytesStart::new(String::from_utf8_lossy(#name));
+            let name = &prefixed_name(&f, custom_ns);
+            let write = match &f.val {
+                Some(val) => quote! {
+                    let mut event = BytesStart::new(String::from_utf8_lossy(#name));
+                    let __v: &[u8] = if self.#ident { b"1" } else { b"0" };
+                    event.push_attribute(Attribute::from((#val.as_ref(), __v)));
                     writer.write_event(Event::Empty(event));
-                }
-            }
+                },
+                None => quote! {
+                    if self.#ident {
+                        let event = BytesStart::new(String::from_utf8_lossy(#name));
+                        writer.write_event(Event::Empty(event));
+                    }
+                },
+            };
+            if_selected(selectable, ident, unless_skipped(&f, write))
         });
         let write_children = children.into_iter().map(|f| {
             if f.skip_serializing {
                 quote! {}
             } else {
                 let ident = f.original.ident.as_ref().unwrap();
-                let name = f.name.as_ref().expect("should have name");
-                quote! {
-                    self.#ident.serialize(#name, writer);
-                }
+                let name = &prefixed_name(&f, custom_ns);
+                let write = if let Some(from_attr) = &f.from_attr {
+                    let value = ser_value_into(&f, quote! {__v});
+                    let write_one = quote! {
+                        __buf.clear();
+                        #value;
+                        let mut event = BytesStart::new(String::from_utf8_lossy(#name));
+                        event.push_attribute(::xmlserde::__escaped_attr(#from_attr, &__buf));
+                        writer.write_event(Event::Empty(event));
+                    };
+                    match f.generic {
+                        Generic::Vec(_) => quote! {
+                            for __v in self.#ident.iter() {
+                                #write_one
+                            }
+                        },
+                        Generic::Opt(_) => quote! {
+                            if let Some(__v) = &self.#ident {
+                                #write_one
+                            }
+                        },
+                        Generic::None => quote! {
+                            let __v = &self.#ident;
+                            #write_one
+                        },
+                    }
+                } else if let Some(key) = &f.sort_by {
+                    quote! {
+                        let mut __sorted = self.#ident.iter().collect::<Vec<_>>();
+                        __sorted.sort_by(|__a, __b| __a.#key.cmp(&__b.#key));
+                        for __v in __sorted {
+                            __v.serialize(#name, writer);
+                        }
+                    }
+                } else if f.emit_empty_on_none {
+                    quote! {
+                        match &self.#ident {
+                            Some(__v) => __v.serialize(#name, writer),
+                            None => {
+                                let event = BytesStart::new(String::from_utf8_lossy(#name));
+                                writer.write_event(Event::Empty(event));
+                            }
+                        }
+                    }
+                } else {
+                    quote! {
+                        self.#ident.serialize(#name, writer);
+                    }
+                };
+                if_selected(selectable, ident, unless_skipped(&f, write))
             }
         });
         let write_untags = untags.into_iter().map(|f| {
             let ident = f.original.ident.as_ref().expect("should have name");
-            quote! {
+            let write = quote! {
                 self.#ident.serialize(b"", writer);
-            }
+            };
+            if_selected(selectable, ident, write)
         });
         quote! {
             #(#write_scf)*
             #(#write_children)*
+            #(::xmlserde::XmlSerialize::serialize(&self.#flatten_idents, b"", writer);)*
             #(#write_untags)*
+            #write_any
         }
     };
     let ident = &container.original.ident;
     let (impl_generics, type_generics, where_clause) = container.original.generics.split_for_impl();
-    let write_event = quote! {
-        if is_empty {
+    let write_empty = if container.expand_empty {
+        quote! {
+            writer.write_event(Event::Start(start));
+            writer.write_event(Event::End(BytesEnd::new(String::from_utf8_lossy(tag))));
+        }
+    } else {
+        quote! {
             writer.write_event(Event::Empty(start));
-        } else if is_untagged {
+        }
+    };
+    let write_event = quote! {
+        if is_untagged {
             // Not to write the start event
             #write_text_or_children
+        } else if is_empty {
+            #write_empty
         } else {
             writer.write_event(Event::Start(start));
             #write_text_or_children
@@ -215,29 +385,262 @@ fn get_ser_struct_impl_block(container: Container) -> proc_macro2::TokenStream {
     } else {
         quote! {}
     };
+    // Used when this struct is flattened into another one, in which all the fields are selected.
+    let select_all = if selectable {
+        quote! {let __is_selected = |_: &str| true;}
+    } else {
+        quote! {}
+    };
+    let flatten_hooks = quote! {
+        fn __serialize_flatten_attrs(&self, __start: &mut ::xmlserde::quick_xml::events::BytesStart) {
+            use ::xmlserde::quick_xml::events::*;
+            use ::xmlserde::quick_xml::events::attributes::Attribute;
+            use ::xmlserde::XmlValue;
+            // Some helpers take `&mut start`, like the value in `serialize` does.
+            #[allow(unused_mut)]
+            let mut start = __start;
+            #select_all
+            let mut __buf = String::new();
+            #write_ns
+            #write_custom_ns
+            #(#build_attr_and_push)*
+            #push_rest_attrs
+            #push_attr_map
+            #push_flatten_attrs
+        }
+
+        fn __has_flatten_children(&self) -> bool {
+            #select_all
+            #init
+            #flatten_is_empty
+            #any_is_empty
+            !is_empty
+        }
+    };
+    let body = quote! {
+        use ::xmlserde::quick_xml::events::*;
+        use ::xmlserde::quick_xml::events::attributes::Attribute;
+        use ::xmlserde::XmlValue;
+        let mut start = BytesStart::new(String::from_utf8_lossy(tag));
+        // A scratch buffer reused by all the attribute and text values of this element.
+        let mut __buf = String::new();
+        let is_untagged = tag.len() == 0;
+        #write_ns
+        #write_custom_ns
+        #(#build_attr_and_push)*
+        #push_rest_attrs
+        #push_attr_map
+        #push_flatten_attrs
+        #reorder_attrs
+        #init
+        #flatten_is_empty
+        #any_is_empty
+        #write_event
+    };
+    if !selectable {
+        return quote! {
+            #[allow(unused_must_use)]
+            impl #impl_generics ::xmlserde::XmlSerialize for #ident #type_generics #where_clause {
+                #inline
+                fn serialize<W: std::io::Write>(
+                    &self,
+                    tag: &[u8],
+                    writer: &mut ::xmlserde::quick_xml::Writer<W>,
+                ) {
+                    #body
+                }
+                #get_root
+                #flatten_hooks
+            }
+        };
+    }
     quote! {
         #[allow(unused_must_use)]
         impl #impl_generics ::xmlserde::XmlSerialize for #ident #type_generics #where_clause {
+            #inline
             fn serialize<W: std::io::Write>(
                 &self,
                 tag: &[u8],
                 writer: &mut ::xmlserde::quick_xml::Writer<W>,
             ) {
-                use ::xmlserde::quick_xml::events::*;
-                use ::xmlserde::quick_xml::events::attributes::Attribute;
-                use ::xmlserde::XmlValue;
-                let start = BytesStart::new(String::from_utf8_lossy(tag));
-                let mut attrs = Vec::<Attribute>::new();
-                let is_untagged = tag.len() == 0;
-                #write_ns
-                #write_custom_ns
-                #(#build_attr_and_push)*
-                let start = start.with_attributes(attrs);
-                #init
-                #write_event
+                self.__serialize_fields(None, tag, writer)
             }
             #get_root
+            #flatten_hooks
+        }
+
+        #[allow(unused_must_use)]
+        impl #impl_generics #ident #type_generics #where_clause {
+            /// Serialize like `XmlSerialize::serialize` but only write the fields whose
+            /// Rust names are in `fields`.
+            pub fn serialize_selected<W: std::io::Write>(
+                &self,
+                fields: &[&str],
+                tag: &[u8],
+                writer: &mut ::xmlserde::quick_xml::Writer<W>,
+            ) {
+                self.__serialize_fields(Some(fields), tag, writer)
+            }
+
+            #inline
+            fn __serialize_fields<W: std::io::Write>(
+                &self,
+                __fields: Option<&[&str]>,
+                tag: &[u8],
+                writer: &mut ::xmlserde::quick_xml::Writer<W>,
+            ) {
+                let __is_selected = |__n: &str| __fields.map_or(true, |__f| __f.contains(&__n));
+                #body
+            }
+        }
+    }
+}
+
+/// Wrap `write` with the check of whether the field is selected when the container is `selectable`.
+fn if_selected(
+    selectable: bool,
+    ident: &syn::Ident,
+    write: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if selectable {
+        let name = ident.to_string();
+        quote! {
+            if __is_selected(#name) {
+                #write
+            }
         }
+    } else {
+        write
+    }
+}
+
+/// The condition of writing the field, which also checks whether the field is selected when
+/// the container is `selectable`.
+fn selected_and(
+    selectable: bool,
+    ident: &syn::Ident,
+    cond: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if selectable {
+        let name = ident.to_string();
+        quote! {__is_selected(#name) && #cond}
+    } else {
+        cond
+    }
+}
+
+/// Wrap `write` with the check of `skip_serializing_if` and `skip_serializing_if_fn` if the field has them.
+fn unless_skipped(
+    field: &StructField,
+    write: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match skip_cond(field) {
+        Some(skip) => quote! {
+            if !(#skip) {
+                #write
+            }
+        },
+        None => write,
+    }
+}
+
+/// The condition of skipping the field given by `skip_serializing_if` and `skip_serializing_if_fn`.
+fn skip_cond(field: &StructField) -> Option<proc_macro2::TokenStream> {
+    let ident = field.original.ident.as_ref().unwrap();
+    match (&field.skip_serializing_if, &field.skip_serializing_if_fn) {
+        (Some(pred), Some(f)) => Some(quote! {#pred(&self.#ident) || #f(self)}),
+        (Some(pred), None) => Some(quote! {#pred(&self.#ident)}),
+        (None, Some(f)) => Some(quote! {#f(self)}),
+        (None, None) => None,
+    }
+}
+
+/// The condition of writing the field, which also checks `skip_serializing_if` and `skip_serializing_if_fn` if the field has them.
+fn not_skipped_and(
+    field: &StructField,
+    cond: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match skip_cond(field) {
+        Some(skip) => quote! {!(#skip) && #cond},
+        None => cond,
+    }
+}
+
+/// Get the name of the `attr`, `child` or `sfc`. A field with `ns` and an unprefixed name is written
+/// with its `prefix` or the one declared for that namespace in `with_custom_ns`.
+fn prefixed_name(
+    attr: &StructField,
+    custom_ns: &[(syn::LitByteStr, syn::LitByteStr)],
+) -> syn::LitByteStr {
+    let name = attr.name_ser.clone().or_else(|| attr.name.clone()).unwrap();
+    let ns = match &attr.ns {
+        Some(ns) => ns,
+        None => return name,
+    };
+    if name.value().contains(&b':') {
+        return name;
+    }
+    if let Some(prefix) = &attr.prefix {
+        let mut value = prefix.value();
+        value.push(b':');
+        value.extend(name.value());
+        return syn::LitByteStr::new(&value, name.span());
+    }
+    let (prefix, _) = custom_ns
+        .iter()
+        .find(|(_, v)| v.value() == ns.value())
+        .expect("cannot find the prefix of `ns`, please declare it by `with_custom_ns` or use a prefixed name");
+    let mut value = prefix.value();
+    value.push(b':');
+    value.extend(name.value());
+    syn::LitByteStr::new(&value, name.span())
+}
+
+/// Get the expression that appends the serialized `attr` or `text` value to `__buf`.
+/// `v` should be a reference to the value.
+fn ser_value_into(field: &StructField, v: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if let Some(f) = &field.serialize_with {
+        quote! {__buf.push_str(&#f(#v))}
+    } else if let Some(with) = &field.with {
+        quote! {__buf.push_str(&#with::serialize(#v))}
+    } else if field.encode_base64 {
+        quote! {::xmlserde::__serialize_base64_into(#v, &mut __buf)}
+    } else if let Some(radix) = field.radix {
+        let spec = match radix {
+            2 => "b",
+            8 => "o",
+            _ if field.upper => "X",
+            _ => "x",
+        };
+        let fmt = match field.width {
+            Some(w) => format!("{{:0{}{}}}", w, spec),
+            None => format!("{{:{}}}", spec),
+        };
+        quote! {::std::fmt::Write::write_fmt(&mut __buf, format_args!(#fmt, #v))}
+    } else if let Some((map_sep, kv_sep)) = &field.map_sep {
+        quote! {::xmlserde::__serialize_map_into(#v, #map_sep, #kv_sep, &mut __buf)}
+    } else if let (Some(sep), Some(elems)) = (&field.sep, field.get_tuple()) {
+        let items = (0..elems.len()).map(|i| {
+            let idx = syn::Index::from(i);
+            let push_sep = if i > 0 {
+                quote! {__buf.push_str(#sep);}
+            } else {
+                quote! {}
+            };
+            quote! {
+                #push_sep
+                ::xmlserde::XmlValue::serialize_into(&(#v).#idx, &mut __buf);
+            }
+        });
+        quote! {{#(#items)*}}
+    } else if let Some(sep) = &field.sep {
+        quote! {::xmlserde::__serialize_list_into(#v, #sep, &mut __buf)}
+    } else if field.xsd_decimal {
+        quote! {__buf.push_str(&::xmlserde::XsdDecimal::serialize_decimal(#v))}
+    } else if field.true_false {
+        quote! {__buf.push_str(if *#v { "true" } else { "false" })}
+    } else {
+        quote! {::xmlserde::XmlValue::serialize_into(#v, &mut __buf)}
     }
 }
 
@@ -246,35 +649,49 @@ fn init_is_empty(
     scf: &Vec<StructField>,
     untags: &Vec<StructField>,
     text: &Option<StructField>,
+    selectable: bool,
 ) -> proc_macro2::TokenStream {
     let children_init = children.iter().map(|c| {
         let ident = c.original.ident.as_ref().unwrap();
-        match &c.generic {
-            Generic::Vec(_) => quote! {
-                let #ident = self.#ident.len() > 0;
-            },
-            Generic::Opt(_) => quote! {
-                let #ident = self.#ident.is_some();
-            },
+        let cond = match &c.generic {
+            Generic::Vec(_) => quote! {self.#ident.len() > 0},
+            Generic::Opt(_) if c.emit_empty_on_none => quote! {true},
+            Generic::Opt(_) => quote! {self.#ident.is_some()},
             Generic::None => match &c.default {
-                Some(d) => quote! {
-                    let #ident = self.#ident != #d();
-                },
-                None => quote! {let #ident = true;},
+                Some(d) => quote! {self.#ident != #d()},
+                None => quote! {true},
             },
+        };
+        let cond = selected_and(selectable, ident, not_skipped_and(c, cond));
+        quote! {
+            let #ident = #cond;
         }
     });
-    let has_untag_fields = untags.len() > 0;
+    let has_untag_fields = if selectable {
+        let names = untags
+            .iter()
+            .map(|u| u.original.ident.as_ref().unwrap().to_string());
+        quote! {(false #(|| __is_selected(#names))*)}
+    } else {
+        let has = untags.len() > 0;
+        quote! {#has}
+    };
     let scf_init = scf.iter().map(|s| {
         let ident = s.original.ident.as_ref().unwrap();
+        let cond = if s.val.is_some() {
+            quote! {true}
+        } else {
+            quote! {self.#ident}
+        };
+        let cond = selected_and(selectable, ident, not_skipped_and(s, cond));
         quote! {
-            let #ident = self.#ident;
+            let #ident = #cond;
         }
     });
     let text_init = match text {
         Some(tf) => {
             let ident = tf.original.ident.as_ref().unwrap();
-            if tf.generic.is_opt() {
+            let init = if tf.generic.is_opt() {
                 quote! {
                     let mut has_text = true;
                     if self.#ident.is_none() {
@@ -291,6 +708,22 @@ fn init_is_empty(
                         has_text = false;
                     }
                 }
+            };
+            let init = match skip_cond(&tf) {
+                Some(skip) => quote! {
+                    #init
+                    let has_text = has_text && !(#skip);
+                },
+                None => init,
+            };
+            if selectable {
+                let name = ident.to_string();
+                quote! {
+                    #init
+                    let has_text = has_text && __is_selected(#name);
+                }
+            } else {
+                init
             }
         }
         None => quote! {let has_text = false;},
@@ -5,15 +5,55 @@ use syn::{Ident, Path};
 pub struct Symbol(&'static str);
 
 pub const DENY_UNKNOWN: Symbol = Symbol("deny_unknown_fields");
+pub const DENY_DUPLICATE: Symbol = Symbol("deny_duplicate_fields");
+pub const CASE_INSENSITIVE_NAMES: Symbol = Symbol("case_insensitive_names");
+pub const SELECTABLE: Symbol = Symbol("selectable");
+pub const RENAME_ALL: Symbol = Symbol("rename_all");
 pub const WITH_NS: Symbol = Symbol("with_ns");
 pub const WITH_CUSTOM_NS: Symbol = Symbol("with_custom_ns");
 pub const ROOT: Symbol = Symbol("root");
 pub const XML_SERDE: Symbol = Symbol("xmlserde");
 pub const NAME: Symbol = Symbol("name");
+pub const ALIAS: Symbol = Symbol("alias");
 pub const TYPE: Symbol = Symbol("ty");
 pub const SKIP_SERIALIZING: Symbol = Symbol("skip_serializing");
+pub const EMIT_EMPTY_ON_NONE: Symbol = Symbol("emit_empty_on_none");
+pub const SKIP_SERIALIZING_IF: Symbol = Symbol("skip_serializing_if");
+pub const SKIP_SERIALIZING_IF_FN: Symbol = Symbol("skip_serializing_if_fn");
 pub const VEC_SIZE: Symbol = Symbol("vec_size");
+pub const CAPACITY: Symbol = Symbol("capacity");
 pub const DEFAULT: Symbol = Symbol("default");
+pub const ON_UNKNOWN: Symbol = Symbol("on_unknown");
+pub const ON_MISSING: Symbol = Symbol("on_missing");
+pub const XSD: Symbol = Symbol("xsd");
+pub const SEP: Symbol = Symbol("sep");
+pub const ENCODE: Symbol = Symbol("encode");
+pub const BARE_BOOL: Symbol = Symbol("bare_bool");
+pub const BOOL_FORMAT: Symbol = Symbol("bool_format");
+pub const RADIX: Symbol = Symbol("radix");
+pub const WIDTH: Symbol = Symbol("width");
+pub const UPPER: Symbol = Symbol("upper");
+pub const TYPE_ATTR: Symbol = Symbol("type_attr");
+pub const TYPE_VALUE: Symbol = Symbol("type_value");
+pub const SERIALIZE_WITH: Symbol = Symbol("serialize_with");
+pub const DESERIALIZE_WITH: Symbol = Symbol("deserialize_with");
+pub const WITH: Symbol = Symbol("with");
+pub const NS: Symbol = Symbol("ns");
+pub const PREFIX: Symbol = Symbol("prefix");
+pub const EXPAND_EMPTY: Symbol = Symbol("expand_empty");
+pub const INLINE: Symbol = Symbol("inline");
+pub const IGNORE_PREFIX: Symbol = Symbol("ignore_prefix");
+pub const FROM_ATTR: Symbol = Symbol("from_attr");
+pub const FLATTEN: Symbol = Symbol("flatten");
+pub const MAP_SEP: Symbol = Symbol("map_sep");
+pub const KV_SEP: Symbol = Symbol("kv_sep");
+pub const VAL: Symbol = Symbol("val");
+pub const SKIP_ERRORS: Symbol = Symbol("skip_errors");
+pub const SORT_BY: Symbol = Symbol("sort_by");
+pub const TRIM: Symbol = Symbol("trim");
+pub const NAME_DE: Symbol = Symbol("name_de");
+pub const NAME_SER: Symbol = Symbol("name_ser");
+pub const PRESERVE_WHITESPACE: Symbol = Symbol("preserve_whitespace");
 
 impl PartialEq<Symbol> for Ident {
     fn eq(&self, other: &Symbol) -> bool {
@@ -83,11 +83,133 @@
 //! </person>
 //! ```
 //!
+//! A variant with an empty name, like `#[xmlserde(name = b"")]`, is transparent. It has no element of its own and
+//! is chosen when meeting any child tag of its inner type. Its inner type should be a struct with `child` fields only and
+//! each matched element makes up a single variant.
+//!
 //! # Attributes
+//! - ty: where the value of a field is from, like `attr`, `child`, `text`, `sfc` and `untag`. Fields without any `xmlserde`
+//!   attribute are ignored, but a field having an `xmlserde` attribute without `ty` is a compile error.
+//!   An `Option<T>` `text` field of an element without text, like `<t></t>` or `<t/>`, is deserialized from the empty
+//!   string if `T` accepts it, which makes `Option<String>` be `Some("")`, and is `None` otherwise.
+//!   A `child` can be `Option<Vec<T>>` to tell the absent elements from the present ones. It is `None` when no element
+//!   of its name is found and `Some` of all of them otherwise. `Some` of an empty `Vec` writes nothing, like `None`.
+//!   A `Box<T>` field works like `T`, so that the recursive types like `Option<Box<Node>>` can be fields.
+//! - root: a container attribute for structs naming the root element, like `root = b"person"`. A path to a
+//!   `&'static [u8]` constant works as well, like `root = PERSON_TAG` or `root = "PERSON_TAG"`.
 //! - name: the tag of the XML element.
+//! - name_de, name_ser: the names of an `attr`, a `child` or an `sfc` used only when deserializing and only when serializing,
+//!   like `#[xmlserde(name_de = b"oldName", name_ser = b"newName", ty = "attr")]` to read the legacy documents and write
+//!   the new name. Either of them falls back to `name`.
+//! - alias: another name of a `child` or an `attr` accepted when deserializing, like `#[xmlserde(name = b"item", alias = b"entry", ty = "child")]`.
+//!   It can be declared more than once and serializing always uses `name`.
 //! - vec_size: creating a vector with the given capacity before deserilizing a element lists. `vec_size=4` or if your initial capacity is defined in an attr, you can use like this `vec_size="cnt"`.
+//! - capacity: like `vec_size`, pre-sizing the collection of an `attr_map` or an `any` field, like `#[xmlserde(ty = "any", capacity = 64)]`.
+//!   The map of an `attr_map` should have a `with_capacity`, like `HashMap`, and its capacity can't refer to an `attr` since the
+//!   map is created before reading the attributes.
 //! - default: assigning a parameter-free function to create a default value for a certain field. Notice that it requires the type of this value impls `Eq` and it will skip serializing when the value equals to the default one.
 //! - untag: see the `Enum` above.
+//! - on_unknown: a container attribute for enums. `on_unknown = "skip"`(default) ignores the children matching none of the variants
+//!   while `on_unknown = "error"` returns an error on them. `on_unknown = "capture"` keeps them in the variant declared with
+//!   `#[xmlserde(ty = "any")]`, whose field should be an `Unparsed`, and writes them back as they are.
+//! - sep: an `attr` whose type is `Vec<T>` or `[T; N]` can be stored as a list joined by `sep`, like
+//!   `#[xmlserde(name = b"point", ty = "attr", sep = " ")]` for `point="1.0 2.0"`. A whitespace `sep` accepts any number of whitespaces.
+//!   A tuple like `(u32, u32)` works too, for `range="1-10"` with `sep = "-"`. `[T; N]` and tuples fail to deserialize if the number of
+//!   items doesn't match.
+//! - on_missing: a container attribute for enums. `on_missing = "error"`(default) returns an error when none of the variants is found
+//!   while `on_missing = "default"` uses the `Default` implementation of the enum. `on_missing = "panic"` is a deprecated
+//!   alias of `"error"`.
+//! - type_attr, type_value: an enum with `#[xmlserde(type_attr = b"xsi:type")]` can select its variant by the attribute of the element,
+//!   like `<shape xsi:type="Circle" r="1"/>` for the variant `#[xmlserde(name = b"circle", type_value = b"Circle")] Circle(Circle)`, and
+//!   the inner type is deserialized from this element. The attribute is checked first and then the element name works as usual.
+//!   Serializing always uses the element name.
+//! - map_sep, kv_sep: an `attr` whose type is a map like `HashMap<String, String>` can be stored as the pairs joined by `map_sep`,
+//!   like `#[xmlserde(name = b"style", ty = "attr", map_sep = ";", kv_sep = ":")]` for `style="color:red;width:2"`. The pairs are written
+//!   in the order of their keys. A pair without `kv_sep` fails to deserialize.
+//! - xsd: `xsd = "decimal"` serializes a number `attr` or `text` in the lexical form of XSD `decimal`.
+//! - selectable: a container attribute for structs generating `serialize_selected(&self, fields: &[&str], tag, writer)`,
+//!   which only writes the fields whose Rust names are in `fields`, like `["name", "age"]`.
+//! - deny_duplicate_fields: a container attribute for structs rejecting a document where a non-`Vec` child occurs more than once
+//!   with `XmlDeError::DuplicateField`, instead of keeping the last one. A duplicated attribute is rejected as well.
+//! - case_insensitive_names: a container attribute for structs. The root, attribute and child names are matched
+//!   ignoring ASCII case when deserializing, like `<Person AGE="16"/>` for `root = b"person"` and `name = b"age"`.
+//! - preserve_whitespace: for an untagged `Vec<Enum>` collecting mixed content. The children and the text are pushed in document
+//!   order, and `preserve_whitespace` keeps the whitespace-only text which is dropped by default.
+//! - attr_map: `#[xmlserde(ty = "attr_map")]` on a map like `HashMap<String, String>` or `BTreeMap<String, String>` collects the attributes
+//!   matching none of the `attr` fields with their unescaped values. They are written back after the other attributes in the order of
+//!   their names. With this field, `deny_unknown_fields` does not reject any attribute.
+//! - attr_order: `#[xmlserde(ty = "attr_order")]` on a `Vec<String>` field records the names of the attributes in the order
+//!   they appear in the source, and the attributes are written back in that order. The attributes not recorded are written after them.
+//!   Use it when the output should be byte-identical to a document whose attribute order differs from the field order.
+//! - any: `#[xmlserde(ty = "any")]` on a `Vec<Unparsed>` field captures the children matching none of the fields, so that the elements
+//!   unknown to your structs are kept. They are written back with their own names after the other children. `Unparsed::tag` gives the name.
+//! - rest_attrs_joined: `#[xmlserde(ty = "rest_attrs_joined")]` on a `String` field collects the attributes matching none of the
+//!   `attr` fields, joined like `a="1" b="x &amp; y"` with the values escaped. They are written back after the other attributes.
+//!   With this field, `deny_unknown_fields` does not reject any attribute.
+//! - flatten: `#[xmlserde(flatten)]` on a struct field inlines the attributes and the children of that struct into this element,
+//!   like a `Meta` struct holding the `id` and `created` attributes shared by many elements. It writes no element of its own.
+//!   The flattened struct receives all the attributes of the element, so it should not use `deny_unknown_fields`.
+//! - tag_name: `#[xmlserde(ty = "tag_name")]` on a `String` or `Option<String>` field captures the name of the element itself,
+//!   like `item` or `a:item`. It is useful for the elements matched by `alias` or a `Vec` of elements collected by an enum.
+//!   It is ignored when serializing.
+//! - tag_ns: `#[xmlserde(ty = "tag_ns")]` on a `String` or `Option<String>` field captures the namespace of the element itself,
+//!   resolved from its prefix or the default namespace declared by `xmlns` on it or its ancestors. It is ignored when serializing.
+//! - ns: the namespace of an `attr`, like `#[xmlserde(name = b"id", ty = "attr", ns = b"http://...")]`. When deserializing, the attribute
+//!   is matched by its namespace and local name whatever prefix the document binds, e.g. both `r:id` and `rel:id`. When serializing,
+//!   the prefix in `name` is used or the one declared for this namespace by `with_custom_ns`. A `child` or an `sfc` can have `ns`
+//!   in the same way, and an unprefixed element is in the default namespace declared by `xmlns` on itself or an ancestor.
+//!   The aliases and `case_insensitive_names` apply to the local name.
+//! - prefix: the namespace prefix of an `attr`, a `child` or an `sfc`, like `#[xmlserde(name = b"val", ty = "attr", prefix = b"w")]` for `w:val`.
+//!   The prefix should be declared by `with_custom_ns` or by an ancestor. With `ns`, it only decides the prefix written
+//!   when serializing and the attribute is still matched by its namespace when deserializing.
+//! - with: a module providing `fn serialize(&T) -> String` and `fn deserialize(&str) -> Result<T, String>` for an `attr` or a `text`
+//!   of type `T` or `Option<T>`, like `#[xmlserde(name = b"url", ty = "attr", with = "percent")]`. The `deserialize` receives the value
+//!   after the XML entities are unescaped, e.g. `a%20&amp;` is passed as `a%20&`, and the string returned by `serialize` is escaped when
+//!   writing. So `with` only needs to handle its own encoding layered on the XML escaping.
+//! - serialize_with, deserialize_with: a function like `fn(&T) -> String` or `fn(&str) -> Result<T, String>`
+//!   used instead of `XmlValue` for an `attr` or a `text`, like `#[xmlserde(name = b"flags", ty = "attr", serialize_with = "flags::to_tokens")]`.
+//!   Either of them can be used alone and the other direction falls back to `XmlValue`.
+//! - expand_empty: `#[xmlserde(expand_empty)]` on a struct writes it as `<a></a>` instead of `<a/>` when it has no content,
+//!   for the consumers rejecting the self-closing tags. The sfcs and the other children keep their own forms.
+//! - inline: `#[xmlserde(inline)]` on a struct or an enum marks the generated `serialize` and `deserialize` with `#[inline]`,
+//!   which may help the small types written in bulk like the cells of a sheet. Measure before using it.
+//! - ignore_prefix: a `child` or an `sfc` matches the tags by their local names when deserializing, so that both `<a:item>` and
+//!   `<item>` are read by `#[xmlserde(name = b"item", ty = "child", ignore_prefix)]`. It is written with its `name` as usual.
+//!   Putting it on the container applies it to all the children and sfcs.
+//! - from_attr: a `child` read from an attribute of the element rather than as a struct, like
+//!   `#[xmlserde(name = b"dimension", ty = "child", from_attr = b"value")] dimension: u32` for `<dimension value="5"/>`.
+//!   The other attributes and the content of the element are ignored. It is written back as `<dimension value="5"/>`.
+//! - emit_empty_on_none: for an `Option` child, `None` is written as an empty element like `<foo/>` instead of being omitted.
+//!   When deserializing, an empty element without attributes is read back as `None`.
+//! - skip_serializing_if: a path to a function taking a reference to the field and returning `true` when
+//!   the field should not be written, like `#[xmlserde(name = b"items", ty = "child", skip_serializing_if = "Vec::is_empty")]`.
+//!   Unlike `default`, it does not require the field to be `Eq`. It is not supported in untagged fields.
+//! - bool_format: `#[xmlserde(bool_format = "true_false")]` writes a boolean `attr` or `text` as `true`/`false` instead of `1`/`0`.
+//!   `"one_zero"` is the default. Both forms are accepted when deserializing.
+//! - bare_bool: a `bool` `attr` is written as a bare name like `<option selected>` when it is `true` and omitted when it is `false`.
+//!   The output is not well-formed XML, so use it only for the HTML-like targets. A missing attribute is read as `false`, and
+//!   `selected=""` or `selected="selected"` as `true`. The bare form itself is skipped as an invalid attribute when deserializing,
+//!   so it is read as `false`.
+//! - skip_serializing_if_fn: like `skip_serializing_if` but the function takes a reference to the whole struct, so that a field can
+//!   be skipped by the values of the others, like `#[xmlserde(name = b"unit", ty = "attr", skip_serializing_if_fn = "Self::no_unit")]`.
+//! - val: the attribute holding the value of an `sfc`, like `#[xmlserde(name = b"b", ty = "sfc", val = b"val")]`. Both states are written
+//!   explicitly as `<b val="1"/>` and `<b val="0"/>`. When deserializing, `<b/>` without the attribute is `true`.
+//! - rename_all: a container attribute deriving the names of the `attr`, `child` and `sfc` fields
+//!   without a `name` from their idents. It is one of `camelCase`, `PascalCase`, `kebab-case` and `snake_case`,
+//!   like `#[xmlserde(rename_all = "camelCase")]`. An explicit `name` wins.
+//! - radix, width, upper: an integer `attr` or `text` written in the radix 2, 8 or 16, like
+//!   `#[xmlserde(name = b"rgb", ty = "attr", radix = 16, width = 6, upper)]` for `rgb="FF00AA"`. `width` pads the digits
+//!   with zeros and `upper` uses the uppercase hexadecimal digits. Both cases are accepted when deserializing.
+//! - encode: `encode = "base64"` stores a `Vec<u8>` `attr` or `text` in base64, like
+//!   `#[xmlserde(ty = "text", encode = "base64")] data: Vec<u8>`. It requires the `base64` feature.
+//! - skip_errors: for a `Vec` `child`, the elements failing to deserialize are dropped instead of aborting the whole
+//!   document.
+//! - trim: `#[xmlserde(ty = "text", trim)]` removes the whitespaces like the indentation around the text before deserializing it.
+//!   It also works on an untagged enum receiving the texts. The text is kept as it is by default, and in the elements
+//!   with `xml:space="preserve"` on themselves or their ancestors.
+//! - sort_by: a `Vec` `child` is written sorted by a field of its elements, like
+//!   `#[xmlserde(name = b"item", ty = "child", sort_by = "id")]`, for the canonical output where the order of the elements
+//!   means nothing. The field should be `Ord` and the ties keep their order. It has no effect when deserializing.
 //!
 //! # Examples
 //! Please see [LogiSheets](https://github.com/proclml/LogiSheets/tree/master/crates/workbook) for examples.
@@ -116,38 +238,142 @@
 /// And string value of `male` will be deserialized as `Gender::Male` while `female` will be as `Gender::Female`.
 /// In the same way, `Gender` will be serialized as `male` of `female`.
 ///
-/// Panic if the given string is out of `male` and `female`.
+/// Deserializing a string out of `male` and `female` returns an error like
+/// `unknown value other for Gender`.
+///
+/// A trailing `_ => Variant` arm adds a fallback variant that takes every other string.
+/// The fallback is serialized as an empty string.
+/// ```
+/// use xmlserde::{xml_serde_enum, XmlValue};
+/// xml_serde_enum!{
+///     #[derive(Debug, PartialEq)]
+///     Color{
+///         Red => "red",
+///         _ => Unknown
+///     }
+/// }
+/// assert_eq!(Color::deserialize("blue"), Ok(Color::Unknown));
+/// ```
+///
+/// The values can also be integer literals, which are written and read as their decimal strings.
+/// ```
+/// use xmlserde::{xml_serde_enum, XmlValue};
+/// xml_serde_enum!{
+///     #[derive(Debug, PartialEq)]
+///     Priority{
+///         Low => 1,
+///         High => 3,
+///     }
+/// }
+/// assert_eq!(Priority::High.serialize(), "3");
+/// assert_eq!(Priority::deserialize("1"), Ok(Priority::Low));
+/// ```
+///
+/// With `#[xmlserde(case_insensitive)]` as the first attribute, the strings are matched ignoring their ASCII case,
+/// which is useful for the producers writing `TRUE`, `True` or `true`. They are still serialized as declared.
+/// ```
+/// use xmlserde::{xml_serde_enum, XmlValue};
+/// xml_serde_enum!{
+///     #[xmlserde(case_insensitive)]
+///     #[derive(Debug, PartialEq)]
+///     Flag{
+///         Yes => "true",
+///         No => "false",
+///         _ => Unknown
+///     }
+/// }
+/// assert_eq!(Flag::deserialize("TRUE"), Ok(Flag::Yes));
+/// assert_eq!(Flag::deserialize("False"), Ok(Flag::No));
+/// assert_eq!(Flag::deserialize("maybe"), Ok(Flag::Unknown));
+/// assert_eq!(Flag::Yes.serialize(), "true");
+/// ```
 #[macro_export]
 macro_rules! xml_serde_enum {
     (
+        @impl $ci:literal;
          $(#[$outer:meta])*
         $name:ident {
             $($f:ident => $s:literal,)*
+            _ => $fallback:ident $(,)?
         }
     ) => {
         #[warn(dead_code)]
         $(#[$outer])*
         pub enum $name {
             $($f,)*
+            $fallback,
         }
 
         impl xmlserde::XmlValue for $name {
             fn serialize(&self) -> String {
                 match &self {
-                    $(Self::$f => String::from($s),)*
+                    $(Self::$f => $crate::__enum_literal($s).into_owned(),)*
+                    Self::$fallback => String::new(),
+                }
+            }
+            fn serialize_into(&self, buf: &mut String) {
+                match &self {
+                    $(Self::$f => buf.push_str(&$crate::__enum_literal($s)),)*
+                    Self::$fallback => {}
                 }
             }
             fn deserialize(s: &str) -> Result<Self, String> {
-                match s {
-                    $($s => Ok(Self::$f),)*
-                    _ => Err(String::from("")),
+                $(if $crate::__enum_literal_eq(s, $s, $ci) {
+                    return Ok(Self::$f);
+                })*
+                Ok(Self::$fallback)
+            }
+        }
+    };
+    (
+        @impl $ci:literal;
+         $(#[$outer:meta])*
+        $name:ident {
+            $($f:ident => $s:literal,)*
+        }
+    ) => {
+        #[warn(dead_code)]
+        $(#[$outer])*
+        pub enum $name {
+            $($f,)*
+        }
+
+        impl xmlserde::XmlValue for $name {
+            fn serialize(&self) -> String {
+                match &self {
+                    $(Self::$f => $crate::__enum_literal($s).into_owned(),)*
                 }
             }
+            fn serialize_into(&self, buf: &mut String) {
+                match &self {
+                    $(Self::$f => buf.push_str(&$crate::__enum_literal($s)),)*
+                }
+            }
+            fn deserialize(s: &str) -> Result<Self, String> {
+                $(if $crate::__enum_literal_eq(s, $s, $ci) {
+                    return Ok(Self::$f);
+                })*
+                Err(format!("unknown value {} for {}", s, stringify!($name)))
+            }
         }
     };
+    (
+        #[xmlserde(case_insensitive)]
+         $(#[$outer:meta])*
+        $name:ident { $($body:tt)* }
+    ) => {
+        $crate::xml_serde_enum!(@impl true; $(#[$outer])* $name { $($body)* });
+    };
+    (
+         $(#[$outer:meta])*
+        $name:ident { $($body:tt)* }
+    ) => {
+        $crate::xml_serde_enum!(@impl false; $(#[$outer])* $name { $($body)* });
+    };
 }
 
 use std::{
+    collections::HashMap,
     fmt::Debug,
     io::{BufRead, Write},
 };
@@ -159,14 +385,32 @@ pub use quick_xml;
 
 use quick_xml::events::Event;
 
+#[cfg(feature = "serde")]
+mod serde_attr;
+#[cfg(feature = "serde")]
+pub use serde_attr::SerdeAttr;
+
 pub trait XmlSerialize {
     fn serialize<W: Write>(&self, tag: &[u8], writer: &mut quick_xml::Writer<W>);
     fn ser_root() -> Option<&'static [u8]> {
         None
     }
+
+    /// Push the attributes of this struct to the element of the outside struct.
+    ///
+    /// It is used by the `flatten` fields, whose children are written by `serialize` with an empty tag.
+    fn __serialize_flatten_attrs(&self, _start: &mut quick_xml::events::BytesStart) {}
+
+    /// Check if this struct writes any children into the element of the outside struct.
+    ///
+    /// It is used by the `flatten` fields.
+    fn __has_flatten_children(&self) -> bool {
+        false
+    }
 }
 
 impl<T: XmlSerialize> XmlSerialize for Option<T> {
+    #[allow(clippy::single_match)]
     fn serialize<W: Write>(&self, tag: &[u8], writer: &mut quick_xml::Writer<W>) {
         match self {
             Some(t) => t.serialize(tag, writer),
@@ -176,6 +420,7 @@ impl<T: XmlSerialize> XmlSerialize for Option<T> {
 }
 
 impl<T: XmlSerialize> XmlSerialize for Vec<T> {
+    #[allow(clippy::let_unit_value)]
     fn serialize<W: Write>(&self, tag: &[u8], writer: &mut quick_xml::Writer<W>) {
         self.iter().for_each(|c| {
             let _ = c.serialize(tag, writer);
@@ -183,13 +428,189 @@ impl<T: XmlSerialize> XmlSerialize for Vec<T> {
     }
 }
 
+impl<T: XmlSerialize> XmlSerialize for Box<T> {
+    fn serialize<W: Write>(&self, tag: &[u8], writer: &mut quick_xml::Writer<W>) {
+        self.as_ref().serialize(tag, writer)
+    }
+
+    fn ser_root() -> Option<&'static [u8]> {
+        T::ser_root()
+    }
+
+    fn __serialize_flatten_attrs(&self, start: &mut quick_xml::events::BytesStart) {
+        self.as_ref().__serialize_flatten_attrs(start)
+    }
+
+    fn __has_flatten_children(&self) -> bool {
+        self.as_ref().__has_flatten_children()
+    }
+}
+
+/// The error returned when deserializing fails.
+///
+/// The `field` is the Rust identifier of the field and the `tag` is its name in XML.
+#[derive(Debug)]
+pub enum XmlDeError {
+    /// The value of an attribute can not be parsed.
+    AttrParse {
+        field: String,
+        tag: String,
+        value: String,
+        reason: String,
+    },
+    /// The text of an element can not be parsed.
+    TextParse {
+        field: String,
+        value: String,
+        reason: String,
+    },
+    /// A required field is not found in the element.
+    MissingField { field: String, tag: Option<String> },
+    /// None of the variants of an enum is found in the element.
+    MissingVariant { ty: String },
+    /// An element or an attribute that is not accepted is met.
+    UnknownField { field: String },
+    /// A non-`Vec` child occurs more than once with `deny_duplicate_fields`.
+    DuplicateField { field: String },
+    /// The root element is not found in the document.
+    MissingRoot { root: String },
+    /// There is unexpected content after the root element with `DeOptions::strict_eof`.
+    TrailingContent { content: String },
+    /// An entity declared in the internal DTD subset can not be expanded with `DeOptions::dtd_entities`.
+    Entity { name: String, reason: String },
+    /// The document is not well-formed.
+    Xml(quick_xml::Error),
+}
+
+impl std::fmt::Display for XmlDeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XmlDeError::AttrParse {
+                field,
+                tag,
+                value,
+                reason,
+            } => write!(
+                f,
+                "failed to parse attribute `{}` (tag `{}`) from {:?}: {}",
+                field, tag, value, reason
+            ),
+            XmlDeError::TextParse {
+                field,
+                value,
+                reason,
+            } => write!(
+                f,
+                "failed to parse text `{}` from {:?}: {}",
+                field, value, reason
+            ),
+            XmlDeError::MissingField {
+                field,
+                tag: Some(tag),
+            } => write!(f, "missing field `{}` (tag `{}`)", field, tag),
+            XmlDeError::MissingField { field, tag: None } => {
+                write!(f, "missing field `{}`", field)
+            }
+            XmlDeError::MissingVariant { ty } => {
+                write!(f, "did not find any variant of `{}`", ty)
+            }
+            XmlDeError::UnknownField { field } => {
+                write!(f, "encountering unknown field: {}", field)
+            }
+            XmlDeError::DuplicateField { field } => {
+                write!(f, "duplicate field `{}`", field)
+            }
+            XmlDeError::MissingRoot { root } => write!(f, "Cannot find the element: {}", root),
+            XmlDeError::TrailingContent { content } => {
+                write!(f, "unexpected content after the root element: {}", content)
+            }
+            XmlDeError::Entity { name, reason } => {
+                write!(f, "failed to expand entity `{}`: {}", name, reason)
+            }
+            XmlDeError::Xml(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for XmlDeError {}
+
+impl From<quick_xml::Error> for XmlDeError {
+    fn from(e: quick_xml::Error) -> Self {
+        XmlDeError::Xml(e)
+    }
+}
+
+impl From<quick_xml::escape::EscapeError> for XmlDeError {
+    fn from(e: quick_xml::escape::EscapeError) -> Self {
+        XmlDeError::Xml(e.into())
+    }
+}
+
+/// The error returned by the entry functions like `xml_deserialize_from_str`, which is
+/// an `XmlDeError` with the byte position in the document where it occurs.
+/// ```
+/// use xmlserde::{xml_capture_document, XmlDeError};
+///
+/// let err = xml_capture_document("<a><b></a>".as_bytes()).unwrap_err();
+/// assert!(matches!(err.error(), XmlDeError::Xml(_)));
+/// assert_eq!(err.position(), 10);
+/// ```
+#[derive(Debug)]
+pub struct XmlDeErrorAt {
+    position: u64,
+    error: XmlDeError,
+}
+
+impl XmlDeErrorAt {
+    /// The byte offset in the document where the error occurs.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The error without the position.
+    pub fn error(&self) -> &XmlDeError {
+        &self.error
+    }
+
+    /// Get the error without the position.
+    pub fn into_inner(self) -> XmlDeError {
+        self.error
+    }
+}
+
+impl std::fmt::Display for XmlDeErrorAt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.error, self.position)
+    }
+}
+
+impl std::error::Error for XmlDeErrorAt {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
 pub trait XmlDeserialize: Sized {
     fn deserialize<B: BufRead>(
         tag: &[u8],
         reader: &mut quick_xml::Reader<B>,
         attrs: quick_xml::events::attributes::Attributes,
         is_empty: bool,
-    ) -> Self;
+    ) -> Result<Self, XmlDeError>;
+
+    /// Deserialize like `deserialize` but panic on errors, which is how the
+    /// deserializing worked before it became fallible.
+    fn deserialize_or_panic<B: BufRead>(
+        tag: &[u8],
+        reader: &mut quick_xml::Reader<B>,
+        attrs: quick_xml::events::attributes::Attributes,
+        is_empty: bool,
+    ) -> Self {
+        match Self::deserialize(tag, reader, attrs, is_empty) {
+            Ok(r) => r,
+            Err(e) => panic!("{}", e),
+        }
+    }
 
     fn de_root() -> Option<&'static [u8]> {
         None
@@ -207,7 +628,9 @@ pub trait XmlDeserialize: Sized {
     /// know how to deal with an untag type. The current solution is to treat them as `Unparsed`
     /// types first, and then pass them into this function to deserialize. Since the type is untagged,
     /// it doesn't require the attributes.
-    fn __deserialize_from_unparsed_array(_array: Vec<(&'static [u8], Unparsed)>) -> Self {
+    fn __deserialize_from_unparsed_array(
+        _array: Vec<(&'static [u8], Unparsed)>,
+    ) -> Result<Self, XmlDeError> {
         unreachable!("untagged types require having `child` types only")
     }
 
@@ -220,7 +643,52 @@ pub trait XmlDeserialize: Sized {
         false
     }
 
-    fn __deserialize_from_text(_: &str) -> Option<Self>
+    /// A helper function for handling the untagged types.
+    ///
+    /// Enums declared with `#[xmlserde(on_unknown = "error")]` return `true` and
+    /// the outside struct will return an error when it meets a child that none of its fields can accept.
+    fn __deny_unknown_children() -> bool {
+        false
+    }
+
+    /// A helper function for handling the untagged types.
+    ///
+    /// Enums declared with `#[xmlserde(on_unknown = "capture")]` return `true` and
+    /// the outside struct will pass the children that none of its fields can accept to `__from_unknown_child`.
+    fn __capture_unknown_children() -> bool {
+        false
+    }
+
+    /// It is used by the enums declared with `#[xmlserde(on_unknown = "capture")]`
+    /// to keep an unknown child in their `any` variant.
+    fn __from_unknown_child(_: Unparsed) -> Option<Self> {
+        None
+    }
+
+    /// A helper function used when an untagged enum is not found in the outside struct.
+    ///
+    /// Enums declared with `#[xmlserde(on_missing = "default")]` return their default value
+    /// instead of an error.
+    fn __on_missing() -> Option<Self> {
+        None
+    }
+
+    /// A helper function used when flattening. It returns the names of the attributes
+    /// so that the outside struct with `deny_unknown_fields` can accept them.
+    fn __get_attr_names() -> Vec<&'static [u8]> {
+        vec![]
+    }
+
+    /// A helper function used when matching the root element.
+    ///
+    /// Structs declared with `#[xmlserde(case_insensitive_names)]` return `true`.
+    fn __case_insensitive_names() -> bool {
+        false
+    }
+
+    /// A helper function for the enums having a `text` variant. It returns `None` if there is
+    /// no such variant.
+    fn __deserialize_from_text(_: &str) -> Option<Result<Self, XmlDeError>>
     where
         Self: Sized,
     {
@@ -228,6 +696,65 @@ pub trait XmlDeserialize: Sized {
     }
 }
 
+// `Box<T>` works like `T` so that the recursive types like `Option<Box<Node>>`
+// can be used as fields.
+impl<T: XmlDeserialize> XmlDeserialize for Box<T> {
+    fn deserialize<B: BufRead>(
+        tag: &[u8],
+        reader: &mut quick_xml::Reader<B>,
+        attrs: quick_xml::events::attributes::Attributes,
+        is_empty: bool,
+    ) -> Result<Self, XmlDeError> {
+        T::deserialize(tag, reader, attrs, is_empty).map(Box::new)
+    }
+
+    fn de_root() -> Option<&'static [u8]> {
+        T::de_root()
+    }
+
+    fn __get_children_tags() -> Vec<&'static [u8]> {
+        T::__get_children_tags()
+    }
+
+    fn __deserialize_from_unparsed_array(
+        array: Vec<(&'static [u8], Unparsed)>,
+    ) -> Result<Self, XmlDeError> {
+        T::__deserialize_from_unparsed_array(array).map(Box::new)
+    }
+
+    fn __is_enum() -> bool {
+        T::__is_enum()
+    }
+
+    fn __deny_unknown_children() -> bool {
+        T::__deny_unknown_children()
+    }
+
+    fn __capture_unknown_children() -> bool {
+        T::__capture_unknown_children()
+    }
+
+    fn __from_unknown_child(u: Unparsed) -> Option<Self> {
+        T::__from_unknown_child(u).map(Box::new)
+    }
+
+    fn __on_missing() -> Option<Self> {
+        T::__on_missing().map(Box::new)
+    }
+
+    fn __get_attr_names() -> Vec<&'static [u8]> {
+        T::__get_attr_names()
+    }
+
+    fn __case_insensitive_names() -> bool {
+        T::__case_insensitive_names()
+    }
+
+    fn __deserialize_from_text(s: &str) -> Option<Result<Self, XmlDeError>> {
+        T::__deserialize_from_text(s).map(|r| r.map(Box::new))
+    }
+}
+
 /// `Unparsed` keeps the XML struct and will be serialized to XML with nothing change.
 /// It is helpful when you are debugging on deserializeing certain element.
 ///
@@ -247,11 +774,13 @@ pub trait XmlDeserialize: Sized {
 /// You can easily make a diff the former and latter version to check if other elments work well.
 #[derive(Debug, Clone)]
 pub struct Unparsed {
+    tag: String,
     data: Vec<Event<'static>>,
     attrs: Vec<(String, String)>,
 }
 
 impl XmlSerialize for Unparsed {
+    #[allow(clippy::len_zero)]
     fn serialize<W: Write>(&self, tag: &[u8], writer: &mut quick_xml::Writer<W>) {
         use quick_xml::events::*;
         let mut start = BytesStart::new(String::from_utf8_lossy(tag));
@@ -278,48 +807,125 @@ impl XmlDeserialize for Unparsed {
         reader: &mut quick_xml::Reader<B>,
         attrs: quick_xml::events::attributes::Attributes,
         is_empty: bool,
-    ) -> Self {
+    ) -> Result<Self, XmlDeError> {
         use quick_xml::events::*;
-        let mut attrs_vec = Vec::<(String, String)>::new();
+        let attrs_vec = collect_attrs(attrs);
         let mut data = Vec::<Event<'static>>::new();
-        let mut buf = Vec::<u8>::new();
-        attrs.into_iter().for_each(|a| {
-            if let Ok(attr) = a {
-                let key =
-                    String::from_utf8(attr.key.into_inner().to_vec()).unwrap_or(String::from(""));
-                let value = String::from_utf8(attr.value.to_vec()).unwrap_or(String::from(""));
-                attrs_vec.push((key, value))
-            }
-        });
+        let mut buf = __take_buf();
         if is_empty {
-            return Unparsed {
+            return Ok(Unparsed {
+                tag: String::from_utf8_lossy(tag).into_owned(),
                 data,
                 attrs: attrs_vec,
-            };
+            });
         }
+        // The number of the nested elements having the same name as `tag`.
+        let mut depth = 0usize;
         loop {
+            buf.clear();
             match reader.read_event_into(&mut buf) {
-                Ok(Event::End(e)) if e.name().into_inner() == tag => break,
+                Ok(Event::End(e)) if e.name().into_inner() == tag && depth == 0 => break,
                 Ok(Event::Eof) => break,
-                Err(_) => break,
-                Ok(e) => data.push(e.into_owned()),
+                Err(e) => return Err(e.into()),
+                Ok(e) => {
+                    match &e {
+                        Event::Start(s) if s.name().into_inner() == tag => depth += 1,
+                        Event::End(e) if e.name().into_inner() == tag => depth -= 1,
+                        _ => {}
+                    }
+                    data.push(e.into_owned())
+                }
             }
         }
-        Unparsed {
+        Ok(Unparsed {
+            tag: String::from_utf8_lossy(tag).into_owned(),
             data,
             attrs: attrs_vec,
-        }
+        })
     }
 
-    fn __deserialize_from_unparsed_array(_array: Vec<(&'static [u8], Unparsed)>) -> Self {
+    fn __deserialize_from_unparsed_array(
+        _array: Vec<(&'static [u8], Unparsed)>,
+    ) -> Result<Self, XmlDeError> {
         unreachable!(
             r#"seems you are using a struct having `attrs` or `text` as an UntaggedStruct"#
         )
     }
 }
 
+fn collect_attrs(attrs: quick_xml::events::attributes::Attributes) -> Vec<(String, String)> {
+    attrs
+        .flatten()
+        .map(|attr| {
+            let key = String::from_utf8(attr.key.into_inner().to_vec()).unwrap_or(String::from(""));
+            let value = __attr_value(&attr).into_owned();
+            (key, value)
+        })
+        .collect()
+}
+
+/// Create an `Unparsed` with the attributes of the element and no children.
+///
+/// It is used by the `flatten` fields, which collect the attributes and the children of the element
+/// and deserialize them at the end.
+pub fn __unparsed_from_attrs(attrs: quick_xml::events::attributes::Attributes) -> Unparsed {
+    Unparsed {
+        tag: String::new(),
+        data: vec![],
+        attrs: collect_attrs(attrs),
+    }
+}
+
+/// Append the `child` element to the children of `u`.
+pub fn __push_unparsed_child(u: &mut Unparsed, tag: &[u8], child: Unparsed) {
+    use quick_xml::events::*;
+    let mut start = BytesStart::new(String::from_utf8_lossy(tag).into_owned());
+    child.attrs.iter().for_each(|(k, v)| {
+        start.push_attribute((k.as_str(), v.as_str()));
+    });
+    if child.data.is_empty() {
+        u.data.push(Event::Empty(start));
+    } else {
+        u.data.push(Event::Start(start));
+        u.data.extend(child.data);
+        let end = BytesEnd::new(String::from_utf8_lossy(tag).into_owned());
+        u.data.push(Event::End(end));
+    }
+}
+
+/// Write the elements captured by an `any` field with their own names.
+pub fn __write_unparsed_any<W: Write>(any: &[Unparsed], writer: &mut quick_xml::Writer<W>) {
+    any.iter()
+        .for_each(|u| u.serialize(u.tag.as_bytes(), writer));
+}
+
 impl Unparsed {
-    pub fn deserialize_to<T>(self) -> Result<T, String>
+    /// Get the name of the captured element.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Get the attributes of the captured element with their unescaped values.
+    pub fn attrs(&self) -> &[(String, String)] {
+        &self.attrs
+    }
+
+    /// Get the text of the captured element, which is the concatenation of all the text
+    /// and the CDATA sections inside it in document order, with the markups removed.
+    pub fn text(&self) -> String {
+        let mut result = String::new();
+        self.data.iter().for_each(|e| match e {
+            Event::Text(t) => match t.unescape() {
+                Ok(s) => result.push_str(&s),
+                Err(_) => result.push_str(&String::from_utf8_lossy(t)),
+            },
+            Event::CData(c) => result.push_str(&String::from_utf8_lossy(c)),
+            _ => {}
+        });
+        result
+    }
+
+    pub fn deserialize_to<T>(self) -> Result<T, XmlDeError>
     where
         T: XmlDeserialize + Sized,
     {
@@ -329,7 +935,8 @@ impl Unparsed {
         self.serialize(t, &mut writer);
         let result = writer.into_inner();
 
-        xml_deserialize_from_reader_with_root::<T, _>(result.as_slice(), t)
+        let mut reader = quick_xml::Reader::from_reader(result.as_slice());
+        xml_deserialize_from_reader_with_root::<T, _>(&mut reader, t, false)
     }
 }
 
@@ -337,12 +944,50 @@ impl Unparsed {
 /// to tell the serializer the tag name of the root. This function will add the header needed for
 /// a XML file.
 pub fn xml_serialize_with_decl<T>(obj: T) -> String
+where
+    T: XmlSerialize,
+{
+    xml_serialize_with_decl_config(obj, &DeclConfig::default())
+}
+
+/// The XML declaration written at the beginning of the document.
+///
+/// The `encoding` and `standalone` are omitted from the declaration when they are `None`.
+/// Notice that it only changes the declaration and the document is always encoded in UTF-8.
+#[derive(Debug, Clone)]
+pub struct DeclConfig<'a> {
+    pub version: &'a str,
+    pub encoding: Option<&'a str>,
+    pub standalone: Option<&'a str>,
+}
+
+impl<'a> Default for DeclConfig<'a> {
+    fn default() -> Self {
+        DeclConfig {
+            version: "1.0",
+            encoding: Some("UTF-8"),
+            standalone: Some("yes"),
+        }
+    }
+}
+
+/// The entry for serializing with a custom XML declaration. `T` should have declared the `root`
+/// by `#[xmlserde(root=b"")]`.
+/// ```
+/// use xmlserde::DeclConfig;
+/// let config = DeclConfig {
+///     standalone: None,
+///     ..Default::default()
+/// };
+/// // xml_serialize_with_decl_config(obj, &config) writes `<?xml version="1.0" encoding="UTF-8"?>`
+/// ```
+pub fn xml_serialize_with_decl_config<T>(obj: T, config: &DeclConfig) -> String
 where
     T: XmlSerialize,
 {
     use quick_xml::events::BytesDecl;
     let mut writer = quick_xml::Writer::new(Vec::new());
-    let decl = BytesDecl::new("1.0", Some("UTF-8"), Some("yes"));
+    let decl = BytesDecl::new(config.version, config.encoding, config.standalone);
     let _ = writer.write_event(Event::Decl(decl));
     obj.serialize(
         T::ser_root().expect(r#"Expect a root element to serialize: #[xmlserde(root=b"tag")]"#),
@@ -362,65 +1007,398 @@ where
     String::from_utf8(writer.into_inner()).expect("decode error")
 }
 
-/// The entry for deserializing. `T` should have declared the `root` by `#[xmlserde(root=b"")]`
-/// to tell the deserializer which tag is the start for deserializing.
-/// ```ignore
-/// use xmlserde_derives::XmlDeserialize;
-/// #[derive(XmlDeserialize)]
-/// #[xmlserde(root=b"person")]
-/// pub struct Person {
-///     #[xmlserde(name = b"age", ty = "attr")]
-///     pub age: u16,
-///     #[xmlserde(name = b"pet", ty = "child")]
-///     pub pets: Vec<Pet>,
-/// }
-/// ```
-pub fn xml_deserialize_from_reader<T, R>(reader: R) -> Result<T, String>
+/// The entry for serializing into `writer` directly, which saves building the whole document
+/// in memory when writing a large file. It returns the first error met by `writer`.
+pub fn xml_serialize_into<T, W>(obj: T, writer: W) -> std::io::Result<()>
 where
-    T: XmlDeserialize,
-    R: BufRead,
+    T: XmlSerialize,
+    W: Write,
 {
-    let root = T::de_root().expect(r#"#[xmlserde(root = b"tag")]"#);
-    xml_deserialize_from_reader_with_root(reader, root)
+    let mut inner = ErrorKeepingWriter {
+        inner: writer,
+        error: None,
+    };
+    let mut writer = quick_xml::Writer::new(&mut inner);
+    obj.serialize(T::ser_root().expect("Expect root"), &mut writer);
+    match inner.error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
-pub(crate) fn xml_deserialize_from_reader_with_root<T, R>(
-    reader: R,
-    root: &[u8],
-) -> Result<T, String>
-where
-    T: XmlDeserialize,
-    R: BufRead,
-{
-    let mut reader = quick_xml::Reader::from_reader(reader);
-    let mut buf = Vec::<u8>::new();
-    loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(start)) => {
-                if start.name().into_inner() == root {
-                    let result = T::deserialize(root, &mut reader, start.attributes(), false);
-                    return Ok(result);
-                }
-            }
-            Ok(Event::Empty(start)) => {
-                if start.name().into_inner() == root {
-                    let result = T::deserialize(root, &mut reader, start.attributes(), true);
-                    return Ok(result);
-                }
-            }
-            Ok(Event::Eof) => {
-                return Err(format!(
-                    "Cannot find the element: {}",
-                    String::from_utf8(root.to_vec()).unwrap()
-                ))
-            }
-            Err(e) => return Err(e.to_string()),
-            _ => {}
+// The generated serializers ignore the results of writing events. This writer keeps
+// the first error and stops writing after it, so that it can be returned at the end.
+struct ErrorKeepingWriter<W: Write> {
+    inner: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> ErrorKeepingWriter<W> {
+    fn keep<R>(&mut self, f: impl FnOnce(&mut W) -> std::io::Result<R>) -> std::io::Result<R> {
+        if self.error.is_some() {
+            return Err(std::io::Error::other("the writer has failed"));
         }
+        f(&mut self.inner).map_err(|e| {
+            let r = std::io::Error::new(e.kind(), e.to_string());
+            self.error = Some(e);
+            r
+        })
     }
 }
 
-/// The entry for deserializing. `T` should have declared the `root` by `#[xmlserde(root=b"")]`
+impl<W: Write> Write for ErrorKeepingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.keep(|w| w.write(buf))
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.keep(|w| w.write_all(buf))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.keep(|w| w.flush())
+    }
+}
+
+/// The entry for serializing into bytes. It works like `xml_serialize` but returns the
+/// bytes written by the writer directly, which saves the cost of the UTF-8 validation.
+pub fn xml_serialize_to_bytes<T>(obj: T) -> Vec<u8>
+where
+    T: XmlSerialize,
+{
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    obj.serialize(T::ser_root().expect("Expect root"), &mut writer);
+    writer.into_inner()
+}
+
+/// The entry for serializing with indentation. Every level is indented by `indent_size` copies of
+/// `indent_char`, like `xml_serialize_pretty(obj, b' ', 4)` or `xml_serialize_pretty(obj, b'\t', 1)`
+/// for tabs. Notice that the indent is a repeated byte since it is how `quick_xml` indents,
+/// so a string mixing different characters is not supported.
+///
+/// The indentation is only written between the elements, so the output can be deserialized back
+/// into the same value.
+pub fn xml_serialize_pretty<T>(obj: T, indent_char: u8, indent_size: usize) -> String
+where
+    T: XmlSerialize,
+{
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), indent_char, indent_size);
+    obj.serialize(T::ser_root().expect("Expect root"), &mut writer);
+    String::from_utf8(writer.into_inner()).expect("decode error")
+}
+
+/// Serialize the contents of `obj` without the wrapping element. Its children are written
+/// directly and its attributes are dropped, which is useful for generating fragments.
+pub fn xml_serialize_untagged<T>(obj: T) -> String
+where
+    T: XmlSerialize,
+{
+    let mut writer = quick_xml::Writer::new(Vec::new());
+    obj.serialize(b"", &mut writer);
+    String::from_utf8(writer.into_inner()).expect("decode error")
+}
+
+/// The entry for deserializing. `T` should have declared the `root` by `#[xmlserde(root=b"")]`
+/// to tell the deserializer which tag is the start for deserializing.
+/// ```ignore
+/// use xmlserde_derives::XmlDeserialize;
+/// #[derive(XmlDeserialize)]
+/// #[xmlserde(root=b"person")]
+/// pub struct Person {
+///     #[xmlserde(name = b"age", ty = "attr")]
+///     pub age: u16,
+///     #[xmlserde(name = b"pet", ty = "child")]
+///     pub pets: Vec<Pet>,
+/// }
+/// ```
+pub fn xml_deserialize_from_reader<T, R>(reader: R) -> Result<T, XmlDeErrorAt>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    xml_deserialize_from_reader_with_options(reader, &DeOptions::default())
+}
+
+/// Capture the whole document in `reader` as an `Unparsed` without knowing its schema,
+/// and return the name of the root element with it. The root can be written back by
+/// `Unparsed`'s `XmlSerialize` with the returned name.
+/// ```
+/// let (root, doc) = xmlserde::xml_capture_document(r#"<a x="1"><b/></a>"#.as_bytes()).unwrap();
+/// assert_eq!(root, b"a");
+/// assert_eq!(doc.attrs(), &[(String::from("x"), String::from("1"))]);
+/// ```
+pub fn xml_capture_document<R: BufRead>(reader: R) -> Result<(Vec<u8>, Unparsed), XmlDeErrorAt> {
+    clear_ns_scope();
+    let mut reader = quick_xml::Reader::from_reader(reader);
+    let mut buf = Vec::<u8>::new();
+    let result = loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(s)) => {
+                let tag = s.name().into_inner().to_vec();
+                break Unparsed::deserialize(&tag, &mut reader, s.attributes(), false)
+                    .map(|u| (tag, u));
+            }
+            Ok(Event::Empty(s)) => {
+                let tag = s.name().into_inner().to_vec();
+                break Unparsed::deserialize(&tag, &mut reader, s.attributes(), true)
+                    .map(|u| (tag, u));
+            }
+            Ok(Event::Eof) => {
+                break Err(XmlDeError::MissingRoot {
+                    root: String::new(),
+                })
+            }
+            Err(e) => break Err(e.into()),
+            _ => {}
+        }
+        buf.clear();
+    };
+    result.map_err(|error| XmlDeErrorAt {
+        position: reader.buffer_position(),
+        error,
+    })
+}
+
+/// Deserialize every root element of `T` in `reader` one after another, like the concatenated
+/// documents `<doc/><doc/>` of a log. The iterator stops at the end of `reader` or after an error.
+pub fn xml_deserialize_many<T, R>(reader: R) -> XmlDeserializeMany<T, R>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    xml_deserialize_many_with_options(reader, &DeOptions::default())
+}
+
+/// Like `xml_deserialize_many` with the given `options`, which apply to each document. The entities
+/// declared in the `DOCTYPE` of a document are expanded in that document only, and `strict_eof`
+/// returns an error on the elements other than the root or the non-whitespace text between the documents.
+pub fn xml_deserialize_many_with_options<T, R>(
+    reader: R,
+    options: &DeOptions,
+) -> XmlDeserializeMany<T, R>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    XmlDeserializeMany {
+        reader: quick_xml::Reader::from_reader(reader),
+        options: options.clone(),
+        done: false,
+        _marker: std::marker::PhantomData,
+    }
+}
+
+/// The iterator returned by `xml_deserialize_many` and `xml_deserialize_many_with_options`.
+pub struct XmlDeserializeMany<T, R> {
+    reader: quick_xml::Reader<R>,
+    options: DeOptions,
+    done: bool,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T, R> Iterator for XmlDeserializeMany<T, R>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    type Item = Result<T, XmlDeErrorAt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let root = T::de_root().expect(r#"#[xmlserde(root = b"tag")]"#);
+        clear_ns_scope();
+        let _options = DeOptionsGuard::new(&self.options);
+        let result =
+            xml_deserialize_from_reader_with_root(&mut self.reader, root, self.options.strict_eof)
+                .and_then(|r| match dtd_entities_error() {
+                    Some(e) => Err(e),
+                    None => Ok(r),
+                });
+        match result {
+            Ok(r) => Some(Ok(r)),
+            Err(XmlDeError::MissingRoot { .. }) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(XmlDeErrorAt {
+                    position: self.reader.buffer_position(),
+                    error: e,
+                }))
+            }
+        }
+    }
+}
+
+/// The options of deserializing.
+#[derive(Debug, Clone, Default)]
+pub struct DeOptions {
+    /// Return an error if there is any element or non-whitespace text after the root element.
+    /// By default the content after the root element is ignored.
+    pub strict_eof: bool,
+    /// Keep the invalid entity references in the text as literal text, like the `&` in `Tom & Jerry`.
+    /// By default they fail the deserialization.
+    pub lenient_entities: bool,
+    /// Expand the references to the general entities declared in the internal subset of the `DOCTYPE`,
+    /// like `<!ENTITY pub "Acme Publishing">`, in the texts and the attribute values.
+    /// The external entities are ignored. An entity expanding to more than 64 KiB, the references nested
+    /// more than 16 levels inside the entities, or more than 4 MiB expanded from the references in the document
+    /// are errors.
+    pub dtd_entities: bool,
+    /// The initial capacity of the buffers reading the events, which saves the reallocations
+    /// of a document with the large elements. By default the buffers grow as needed.
+    pub buffer_capacity: usize,
+}
+
+/// The entry for deserializing with the given `options`.
+pub fn xml_deserialize_from_reader_with_options<T, R>(
+    reader: R,
+    options: &DeOptions,
+) -> Result<T, XmlDeErrorAt>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    let root = T::de_root().expect(r#"#[xmlserde(root = b"tag")]"#);
+    clear_ns_scope();
+    let _options = DeOptionsGuard::new(options);
+    let mut reader = quick_xml::Reader::from_reader(reader);
+    let result = xml_deserialize_from_reader_with_root(&mut reader, root, false).and_then(|r| {
+        if options.strict_eof {
+            check_trailing_content(&mut reader)?;
+        }
+        match dtd_entities_error() {
+            Some(e) => Err(e),
+            None => Ok(r),
+        }
+    });
+    result.map_err(|error| XmlDeErrorAt {
+        position: reader.buffer_position(),
+        error,
+    })
+}
+
+/// Set the thread-local state of `DeOptions` for the deserialization in progress. The previous
+/// state is restored when it is dropped, even if the deserialization panics.
+struct DeOptionsGuard {
+    lenient_entities: bool,
+    dtd_entities: Option<DtdEntities>,
+    buffer_capacity: usize,
+}
+
+impl DeOptionsGuard {
+    fn new(options: &DeOptions) -> Self {
+        let dtd = options.dtd_entities.then(DtdEntities::default);
+        DeOptionsGuard {
+            lenient_entities: LENIENT_ENTITIES.with(|l| l.replace(options.lenient_entities)),
+            dtd_entities: DTD_ENTITIES.with(|d| d.replace(dtd)),
+            buffer_capacity: BUF_CAPACITY.with(|c| c.replace(options.buffer_capacity)),
+        }
+    }
+}
+
+impl Drop for DeOptionsGuard {
+    fn drop(&mut self) {
+        let dtd = self.dtd_entities.take();
+        let _ = LENIENT_ENTITIES.try_with(|l| l.set(self.lenient_entities));
+        let _ = DTD_ENTITIES.try_with(|d| d.replace(dtd));
+        let _ = BUF_CAPACITY.try_with(|c| c.set(self.buffer_capacity));
+    }
+}
+
+// An attribute value over the limit is kept unexpanded and reported after the deserialization.
+fn dtd_entities_error() -> Option<XmlDeError> {
+    DTD_ENTITIES.with(|d| d.borrow().as_ref().and_then(|d| d.error()))
+}
+
+/// Read to the end and return an error on the elements or the non-whitespace text.
+/// The comments and the processing instructions are allowed after the root element.
+fn check_trailing_content<R: BufRead>(reader: &mut quick_xml::Reader<R>) -> Result<(), XmlDeError> {
+    let mut buf = Vec::<u8>::new();
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Eof => return Ok(()),
+            Event::Text(t) if t.iter().all(|b| b.is_ascii_whitespace()) => {}
+            Event::Comment(_) | Event::PI(_) => {}
+            Event::Start(e) | Event::Empty(e) => {
+                return Err(XmlDeError::TrailingContent {
+                    content: String::from_utf8_lossy(e.name().into_inner()).into_owned(),
+                })
+            }
+            e => {
+                return Err(XmlDeError::TrailingContent {
+                    content: String::from_utf8_lossy(&e).into_owned(),
+                })
+            }
+        }
+        buf.clear();
+    }
+}
+
+// The position is not attached here since `Unparsed::deserialize_to` reads a document
+// written by itself and its position means nothing to the users.
+// With `strict`, the elements other than the root and the non-whitespace text before the root are errors.
+pub(crate) fn xml_deserialize_from_reader_with_root<T, R>(
+    reader: &mut quick_xml::Reader<R>,
+    root: &[u8],
+    strict: bool,
+) -> Result<T, XmlDeError>
+where
+    T: XmlDeserialize,
+    R: BufRead,
+{
+    let mut buf = __take_buf();
+    let is_root = |name: &[u8]| {
+        if T::__case_insensitive_names() {
+            name.eq_ignore_ascii_case(root)
+        } else {
+            name == root
+        }
+    };
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(start)) if is_root(start.name().into_inner()) => {
+                let tag = start.name().into_inner().to_vec();
+                return T::deserialize(&tag, reader, start.attributes(), false);
+            }
+            Ok(Event::Empty(start)) if is_root(start.name().into_inner()) => {
+                let tag = start.name().into_inner().to_vec();
+                return T::deserialize(&tag, reader, start.attributes(), true);
+            }
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if strict => {
+                return Err(XmlDeError::TrailingContent {
+                    content: String::from_utf8_lossy(e.name().into_inner()).into_owned(),
+                })
+            }
+            Ok(Event::Text(t)) if strict && !t.iter().all(|b| b.is_ascii_whitespace()) => {
+                return Err(XmlDeError::TrailingContent {
+                    content: String::from_utf8_lossy(&t).into_owned(),
+                })
+            }
+            Ok(Event::DocType(d)) if DTD_ENTITIES.with(|e| e.borrow().is_some()) => {
+                let values = parse_dtd_entities(&String::from_utf8_lossy(&d))?;
+                DTD_ENTITIES.with(|e| {
+                    *e.borrow_mut() = Some(DtdEntities {
+                        values,
+                        ..Default::default()
+                    })
+                });
+            }
+            Ok(Event::Eof) => {
+                return Err(XmlDeError::MissingRoot {
+                    root: String::from_utf8_lossy(root).into_owned(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+            _ => {}
+        }
+    }
+}
+
+/// The entry for deserializing. `T` should have declared the `root` by `#[xmlserde(root=b"")]`
 /// to tell the deserializer which tag is the start for deserializing.
 /// ```ignore
 /// use xmlserde_derives::XmlDeserialize;
@@ -433,16 +1411,33 @@ where
 ///     pub pets: Vec<Pet>,
 /// }
 /// ```
-pub fn xml_deserialize_from_str<T>(xml_str: &str) -> Result<T, String>
+pub fn xml_deserialize_from_str<T>(xml_str: &str) -> Result<T, XmlDeErrorAt>
 where
     T: XmlDeserialize,
 {
     xml_deserialize_from_reader(xml_str.as_bytes())
 }
 
+/// The entry for deserializing a string with the given `options`.
+pub fn xml_deserialize_from_str_with_options<T>(
+    xml_str: &str,
+    options: &DeOptions,
+) -> Result<T, XmlDeErrorAt>
+where
+    T: XmlDeserialize,
+{
+    xml_deserialize_from_reader_with_options(xml_str.as_bytes(), options)
+}
+
 pub trait XmlValue: Sized {
     fn serialize(&self) -> String;
     fn deserialize(s: &str) -> Result<Self, String>;
+
+    /// Append the serialized value to `buf`. The derived `XmlSerialize` uses it to
+    /// reuse a buffer across the attributes of an element.
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push_str(&self.serialize());
+    }
 }
 
 impl XmlValue for bool {
@@ -454,6 +1449,10 @@ impl XmlValue for bool {
         }
     }
 
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push(if *self { '1' } else { '0' });
+    }
+
     fn deserialize(s: &str) -> Result<Self, String> {
         let s = s.to_ascii_lowercase();
         if s == "1" || s == "true" {
@@ -471,11 +1470,54 @@ impl XmlValue for String {
         self.to_owned()
     }
 
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push_str(self);
+    }
+
     fn deserialize(s: &str) -> Result<Self, String> {
         Ok(s.to_owned())
     }
 }
 
+macro_rules! impl_xml_value_for_str_ptr {
+    ($ptr:ty) => {
+        impl XmlValue for $ptr {
+            fn serialize(&self) -> String {
+                String::from(&**self)
+            }
+
+            fn serialize_into(&self, buf: &mut String) {
+                buf.push_str(self);
+            }
+
+            fn deserialize(s: &str) -> Result<Self, String> {
+                Ok(<$ptr>::from(s))
+            }
+        }
+    };
+}
+
+impl_xml_value_for_str_ptr!(Box<str>);
+impl_xml_value_for_str_ptr!(std::sync::Arc<str>);
+
+impl XmlValue for char {
+    fn serialize(&self) -> String {
+        self.to_string()
+    }
+
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push(*self);
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(format!("Cannot parse {} into a char", s)),
+        }
+    }
+}
+
 macro_rules! impl_xml_value_for_num {
     ($num:ty) => {
         impl XmlValue for $num {
@@ -483,6 +1525,106 @@ macro_rules! impl_xml_value_for_num {
                 self.to_string()
             }
 
+            fn serialize_into(&self, buf: &mut String) {
+                use std::fmt::Write;
+                let _ = write!(buf, "{}", self);
+            }
+
+            fn deserialize(s: &str) -> Result<Self, String> {
+                let r = s.parse::<$num>();
+                match r {
+                    Ok(f) => Ok(f),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        }
+    };
+    // Integers are written by `itoa` when the feature `fast-num` is enabled.
+    ($num:ty, int) => {
+        impl XmlValue for $num {
+            fn serialize(&self) -> String {
+                let mut s = String::new();
+                self.serialize_into(&mut s);
+                s
+            }
+
+            #[cfg(feature = "fast-num")]
+            fn serialize_into(&self, buf: &mut String) {
+                buf.push_str(itoa::Buffer::new().format(*self));
+            }
+
+            #[cfg(not(feature = "fast-num"))]
+            fn serialize_into(&self, buf: &mut String) {
+                use std::fmt::Write;
+                let _ = write!(buf, "{}", self);
+            }
+
+            fn deserialize(s: &str) -> Result<Self, String> {
+                let r = s.parse::<$num>();
+                match r {
+                    Ok(f) => Ok(f),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        }
+    };
+    ($num:ty, nonzero) => {
+        impl XmlValue for $num {
+            fn serialize(&self) -> String {
+                let mut s = String::new();
+                self.serialize_into(&mut s);
+                s
+            }
+
+            #[cfg(feature = "fast-num")]
+            fn serialize_into(&self, buf: &mut String) {
+                buf.push_str(itoa::Buffer::new().format(self.get()));
+            }
+
+            #[cfg(not(feature = "fast-num"))]
+            fn serialize_into(&self, buf: &mut String) {
+                use std::fmt::Write;
+                let _ = write!(buf, "{}", self);
+            }
+
+            fn deserialize(s: &str) -> Result<Self, String> {
+                let r = s.parse::<$num>();
+                match r {
+                    Ok(f) => Ok(f),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+        }
+    };
+    // Floats are written by `ryu` when the feature `fast-num` is enabled. The very large and
+    // the very small values, which `ryu` writes in the exponent form like `1e20`, fall back to
+    // `Display` so that the output is the same with or without the feature.
+    ($num:ty, float) => {
+        impl XmlValue for $num {
+            fn serialize(&self) -> String {
+                let mut s = String::new();
+                self.serialize_into(&mut s);
+                s
+            }
+
+            #[cfg(feature = "fast-num")]
+            fn serialize_into(&self, buf: &mut String) {
+                let mut b = ryu::Buffer::new();
+                let s = b.format(*self);
+                if s.contains('e') {
+                    use std::fmt::Write;
+                    let _ = write!(buf, "{}", self);
+                } else {
+                    buf.push_str(s.strip_suffix(".0").unwrap_or(s));
+                }
+            }
+
+            #[cfg(not(feature = "fast-num"))]
+            fn serialize_into(&self, buf: &mut String) {
+                use std::fmt::Write;
+                let _ = write!(buf, "{}", self);
+            }
+
             fn deserialize(s: &str) -> Result<Self, String> {
                 let r = s.parse::<$num>();
                 match r {
@@ -494,29 +1636,1045 @@ macro_rules! impl_xml_value_for_num {
     };
 }
 
-impl_xml_value_for_num!(i8);
-impl_xml_value_for_num!(u8);
-impl_xml_value_for_num!(i16);
-impl_xml_value_for_num!(u16);
-impl_xml_value_for_num!(i32);
-impl_xml_value_for_num!(u32);
-impl_xml_value_for_num!(i64);
-impl_xml_value_for_num!(u64);
-impl_xml_value_for_num!(i128);
-impl_xml_value_for_num!(u128);
-impl_xml_value_for_num!(isize);
-impl_xml_value_for_num!(usize);
-impl_xml_value_for_num!(f32);
-impl_xml_value_for_num!(f64);
-impl_xml_value_for_num!(std::num::NonZeroI8);
-impl_xml_value_for_num!(std::num::NonZeroU8);
-impl_xml_value_for_num!(std::num::NonZeroI16);
-impl_xml_value_for_num!(std::num::NonZeroU16);
-impl_xml_value_for_num!(std::num::NonZeroI32);
-impl_xml_value_for_num!(std::num::NonZeroU32);
-impl_xml_value_for_num!(std::num::NonZeroI64);
-impl_xml_value_for_num!(std::num::NonZeroU64);
-impl_xml_value_for_num!(std::num::NonZeroI128);
-impl_xml_value_for_num!(std::num::NonZeroU128);
-impl_xml_value_for_num!(std::num::NonZeroIsize);
-impl_xml_value_for_num!(std::num::NonZeroUsize);
+impl_xml_value_for_num!(i8, int);
+impl_xml_value_for_num!(u8, int);
+impl_xml_value_for_num!(i16, int);
+impl_xml_value_for_num!(u16, int);
+impl_xml_value_for_num!(i32, int);
+impl_xml_value_for_num!(u32, int);
+impl_xml_value_for_num!(i64, int);
+impl_xml_value_for_num!(u64, int);
+impl_xml_value_for_num!(i128, int);
+impl_xml_value_for_num!(u128, int);
+impl_xml_value_for_num!(isize, int);
+impl_xml_value_for_num!(usize, int);
+impl_xml_value_for_num!(f32, float);
+impl_xml_value_for_num!(f64, float);
+impl_xml_value_for_num!(std::num::NonZeroI8, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroU8, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroI16, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroU16, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroI32, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroU32, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroI64, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroU64, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroI128, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroU128, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroIsize, nonzero);
+impl_xml_value_for_num!(std::num::NonZeroUsize, nonzero);
+
+// The addresses are parsed and written like the numbers by their `FromStr` and `Display`,
+// like `127.0.0.1`, `::1` and `[::1]:8080`.
+impl_xml_value_for_num!(std::net::IpAddr);
+impl_xml_value_for_num!(std::net::Ipv4Addr);
+impl_xml_value_for_num!(std::net::Ipv6Addr);
+impl_xml_value_for_num!(std::net::SocketAddr);
+
+/// The value of the `xml:space` attribute.
+///
+/// `xml:space="preserve"` tells that the whitespaces in the content of this element are significant
+/// while `xml:space="default"` lets the application decide. You can read it like
+/// ```ignore
+/// #[xmlserde(name = b"xml:space", ty = "attr")]
+/// pub space: Option<XmlSpace>,
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum XmlSpace {
+    #[default]
+    Default,
+    Preserve,
+}
+
+impl XmlValue for XmlSpace {
+    fn serialize(&self) -> String {
+        match self {
+            XmlSpace::Default => String::from("default"),
+            XmlSpace::Preserve => String::from("preserve"),
+        }
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        match s {
+            "default" => Ok(XmlSpace::Default),
+            "preserve" => Ok(XmlSpace::Preserve),
+            _ => Err(format!("Cannot parse {} into xml:space", s)),
+        }
+    }
+}
+
+/// A value that keeps the text it was read from, so that it is written back unchanged.
+///
+/// Parsing `<c v="0100"/>` into a `u32` and writing it again gives `v="100"`. With
+/// `Verbatim<u32>`, the parsed value is still available but `v="0100"` is written back.
+/// ```ignore
+/// #[xmlserde(name = b"v", ty = "attr")]
+/// pub v: Verbatim<u32>,
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Verbatim<T> {
+    value: T,
+    text: String,
+}
+
+impl<T: XmlValue> Verbatim<T> {
+    /// Create from a value, whose text is the value serialized.
+    pub fn new(value: T) -> Self {
+        let text = value.serialize();
+        Verbatim { value, text }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// The text read from the xml.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
+
+impl<T: XmlValue> XmlValue for Verbatim<T> {
+    fn serialize(&self) -> String {
+        self.text.clone()
+    }
+
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push_str(&self.text);
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        let value = T::deserialize(s)?;
+        Ok(Verbatim {
+            value,
+            text: s.to_string(),
+        })
+    }
+}
+
+/// A `Duration` written as the number of whole seconds, like `timeout="30"`.
+/// The fraction of a second is dropped when serializing.
+/// ```ignore
+/// #[xmlserde(name = b"timeout", ty = "attr")]
+/// pub timeout: DurationSecs,
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DurationSecs(pub std::time::Duration);
+
+/// A `Duration` written as the number of whole milliseconds, like `delay="1500"`.
+/// The fraction of a millisecond is dropped when serializing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DurationMillis(pub std::time::Duration);
+
+macro_rules! impl_xml_value_for_duration {
+    ($ty:ident, $as_unit:ident, $from_unit:ident) => {
+        impl XmlValue for $ty {
+            fn serialize(&self) -> String {
+                self.0.$as_unit().to_string()
+            }
+
+            fn deserialize(s: &str) -> Result<Self, String> {
+                s.parse::<u64>()
+                    .map(|n| $ty(std::time::Duration::$from_unit(n)))
+                    .map_err(|e| e.to_string())
+            }
+        }
+
+        impl From<std::time::Duration> for $ty {
+            fn from(d: std::time::Duration) -> Self {
+                $ty(d)
+            }
+        }
+
+        impl From<$ty> for std::time::Duration {
+            fn from(d: $ty) -> Self {
+                d.0
+            }
+        }
+    };
+}
+
+impl_xml_value_for_duration!(DurationSecs, as_secs, from_secs);
+impl_xml_value_for_duration!(DurationMillis, as_millis, from_millis);
+
+// `DateTime<Utc>` is written in RFC 3339, like `2023-01-02T03:04:05Z`. The naive types have no offset
+// and use the same layout without it, like `2023-01-02T03:04:05` and `2023-01-02`.
+#[cfg(feature = "chrono")]
+impl XmlValue for chrono::DateTime<chrono::Utc> {
+    fn serialize(&self) -> String {
+        self.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .map(|d| d.with_timezone(&chrono::Utc))
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for chrono::NaiveDateTime {
+    fn serialize(&self) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        s.parse::<chrono::NaiveDateTime>()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl XmlValue for chrono::NaiveDate {
+    fn serialize(&self) -> String {
+        self.format("%Y-%m-%d").to_string()
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        s.parse::<chrono::NaiveDate>().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl XmlValue for uuid::Uuid {
+    fn serialize(&self) -> String {
+        self.hyphenated().to_string()
+    }
+
+    fn serialize_into(&self, buf: &mut String) {
+        buf.push_str(
+            self.hyphenated()
+                .encode_lower(&mut uuid::Uuid::encode_buffer()),
+        )
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        uuid::Uuid::parse_str(s).map_err(|e| e.to_string())
+    }
+}
+
+/// Get the value of an attribute with the XML entities unescaped. The raw value is used
+/// if it has an unknown entity. It borrows the value of the attribute unless it needs unescaping.
+pub fn __attr_value<'a>(
+    attr: &quick_xml::events::attributes::Attribute<'a>,
+) -> std::borrow::Cow<'a, str> {
+    let value = DTD_ENTITIES.with(|d| match &mut *d.borrow_mut() {
+        Some(dtd) if !dtd.values.is_empty() => dtd.unescape(|r| attr.unescape_value_with(r)),
+        _ => attr.unescape_value(),
+    });
+    match value {
+        Ok(v) => v,
+        Err(_) => std::borrow::Cow::Owned(String::from_utf8_lossy(&attr.value).into_owned()),
+    }
+}
+
+thread_local! {
+    // Whether the deserialization in progress uses `DeOptions::lenient_entities`.
+    static LENIENT_ENTITIES: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    // The expanded entities of the internal DTD subset, which is `Some` if the deserialization
+    // in progress uses `DeOptions::dtd_entities`.
+    static DTD_ENTITIES: std::cell::RefCell<Option<DtdEntities>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+// The limits against the entities referring to each other exponentially like the billion laughs:
+// the length of an expanded entity, the depth of the references inside the entities and
+// the total length of the references expanded in a document.
+const MAX_ENTITY_LEN: usize = 64 * 1024;
+const MAX_ENTITY_DEPTH: usize = 16;
+const MAX_EXPANDED_LEN: usize = 4 * 1024 * 1024;
+
+#[derive(Default)]
+struct DtdEntities {
+    // The expanded values of the entities.
+    values: HashMap<String, String>,
+    // The bytes expanded from the references in the document so far.
+    expanded: usize,
+    // The entity whose reference went over `MAX_EXPANDED_LEN`.
+    exceeded: Option<String>,
+}
+
+impl DtdEntities {
+    // Unescape by `f` with a resolver of the references in the document. The resolver returns `None`
+    // once the expanded length is over the limit, which stops the unescaping.
+    fn unescape<'a, T>(
+        &'a mut self,
+        f: impl FnOnce(&mut dyn FnMut(&str) -> Option<&'a str>) -> T,
+    ) -> T {
+        let DtdEntities {
+            values,
+            expanded,
+            exceeded,
+        } = self;
+        let values = &*values;
+        f(&mut |name: &str| match values.get(name) {
+            Some(v) => {
+                *expanded += v.len();
+                if *expanded > MAX_EXPANDED_LEN {
+                    exceeded.get_or_insert_with(|| name.to_string());
+                    return None;
+                }
+                Some(v.as_str())
+            }
+            None => quick_xml::escape::resolve_predefined_entity(name),
+        })
+    }
+
+    fn error(&self) -> Option<XmlDeError> {
+        self.exceeded.as_ref().map(|name| XmlDeError::Entity {
+            name: name.clone(),
+            reason: String::from("the expanded document is too long"),
+        })
+    }
+}
+
+/// Collect the general entities like `<!ENTITY name "value">` from the internal subset
+/// of the `DOCTYPE` and expand their values.
+fn parse_dtd_entities(doctype: &str) -> Result<HashMap<String, String>, XmlDeError> {
+    let mut raw = HashMap::<String, String>::new();
+    let mut names = Vec::<String>::new();
+    let mut rest = match doctype.find('[') {
+        Some(i) => &doctype[i + 1..],
+        None => "",
+    };
+    while let Some(i) = rest.find("<!") {
+        rest = &rest[i..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map(|j| &rest[j + 3..]).unwrap_or("");
+            continue;
+        }
+        let decl = match rest.strip_prefix("<!ENTITY") {
+            Some(d) => d.trim_start(),
+            None => {
+                rest = &rest[2..];
+                continue;
+            }
+        };
+        let is_parameter = decl.starts_with('%');
+        let decl = decl.trim_start_matches('%').trim_start();
+        let name_end = decl.find(char::is_whitespace).unwrap_or(decl.len());
+        let name = &decl[..name_end];
+        let decl = decl[name_end..].trim_start();
+        rest = decl;
+        let quote = match decl.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            // An external entity like `SYSTEM "file.xml"`.
+            _ => continue,
+        };
+        let value_end = match decl[1..].find(quote) {
+            Some(j) => j + 1,
+            None => break,
+        };
+        rest = &decl[value_end + 1..];
+        // The first declaration of an entity is binding.
+        if !is_parameter && !raw.contains_key(name) {
+            raw.insert(name.to_string(), decl[1..value_end].to_string());
+            names.push(name.to_string());
+        }
+    }
+    // The expanded values with the depth of the references inside them.
+    let mut expanded = HashMap::<String, (String, usize)>::with_capacity(names.len());
+    for name in &names {
+        expand_entity(name, &raw, &mut expanded, &mut vec![])?;
+    }
+    Ok(expanded.into_iter().map(|(k, (v, _))| (k, v)).collect())
+}
+
+/// Expand the entity `name` into `expanded` after the entities it refers to.
+/// `stack` holds the entities being expanded, which is at most `MAX_ENTITY_DEPTH` long.
+fn expand_entity(
+    name: &str,
+    raw: &HashMap<String, String>,
+    expanded: &mut HashMap<String, (String, usize)>,
+    stack: &mut Vec<String>,
+) -> Result<usize, XmlDeError> {
+    let err = |reason: &str| XmlDeError::Entity {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+    if let Some((_, depth)) = expanded.get(name) {
+        return Ok(*depth);
+    }
+    if stack.iter().any(|n| n == name) {
+        return Err(err("recursive reference"));
+    }
+    let value = raw.get(name).ok_or_else(|| err("undeclared"))?;
+    stack.push(name.to_string());
+    let mut depth = 0;
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value.as_str();
+    while let Some(i) = rest.find('&') {
+        result.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let end = rest
+            .find(';')
+            .ok_or_else(|| err("unterminated reference"))?;
+        let reference = &rest[1..end];
+        if raw.contains_key(reference) {
+            if stack.len() >= MAX_ENTITY_DEPTH {
+                return Err(err("the references are nested too deeply"));
+            }
+            let d = expand_entity(reference, raw, expanded, stack)?;
+            depth = depth.max(d + 1);
+            if depth > MAX_ENTITY_DEPTH {
+                return Err(err("the references are nested too deeply"));
+            }
+            result.push_str(&expanded[reference].0);
+        } else {
+            let v = quick_xml::escape::unescape(&rest[..=end]).map_err(|e| err(&e.to_string()))?;
+            result.push_str(&v);
+        }
+        if result.len() > MAX_ENTITY_LEN {
+            return Err(err("the expanded value is too long"));
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    stack.pop();
+    if result.len() > MAX_ENTITY_LEN {
+        return Err(err("the expanded value is too long"));
+    }
+    expanded.insert(name.to_string(), (result, depth));
+    Ok(depth)
+}
+
+/// Get the text with the XML entities unescaped, including those declared in the internal DTD subset
+/// with `DeOptions::dtd_entities`. With `DeOptions::lenient_entities`
+/// the invalid entity references are kept as they are instead of returning an error.
+pub fn __unescape_text<'a>(
+    t: &'a quick_xml::events::BytesText,
+) -> Result<std::borrow::Cow<'a, str>, XmlDeError> {
+    let text = DTD_ENTITIES.with(|d| match &mut *d.borrow_mut() {
+        Some(dtd) if !dtd.values.is_empty() => {
+            let text = dtd.unescape(|r| t.unescape_with(r));
+            match dtd.error() {
+                Some(e) => Err(e),
+                None => Ok(text),
+            }
+        }
+        _ => Ok(t.unescape()),
+    })?;
+    match text {
+        Ok(s) => Ok(s),
+        Err(_) if LENIENT_ENTITIES.with(|l| l.get()) => Ok(std::borrow::Cow::Owned(
+            unescape_lenient(&String::from_utf8_lossy(t)),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn unescape_lenient(raw: &str) -> String {
+    let mut result = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(i) = rest.find('&') {
+        result.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let entity = rest
+            .find(';')
+            .map(|j| &rest[..=j])
+            .filter(|e| !e[1..].contains(|c: char| c == '&' || c.is_whitespace()));
+        match entity.map(|e| (e, quick_xml::escape::unescape(e))) {
+            Some((e, Ok(s))) => {
+                result.push_str(&s);
+                rest = &rest[e.len()..];
+            }
+            _ => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Remove the XML whitespaces, which are the spaces, the tabs and the line breaks, around `s`.
+///
+/// It is used by the `text` fields and the untagged enums with `#[xmlserde(trim)]`.
+pub fn __trim_text(s: &str) -> &str {
+    s.trim_matches(|c| matches!(c, ' ' | '\t' | '\r' | '\n'))
+}
+
+/// Make an attribute whose value is escaped.
+pub fn __escaped_attr<'a>(
+    key: &'a [u8],
+    value: &'a str,
+) -> quick_xml::events::attributes::Attribute<'a> {
+    use std::borrow::Cow;
+    let value = match quick_xml::escape::escape(value) {
+        Cow::Borrowed(v) => Cow::Borrowed(v.as_bytes()),
+        Cow::Owned(v) => Cow::Owned(v.into_bytes()),
+    };
+    quick_xml::events::attributes::Attribute {
+        key: quick_xml::name::QName(key),
+        value,
+    }
+}
+
+/// Encode the bytes in base64 and append them to `buf`.
+///
+/// It is used by the fields with `#[xmlserde(encode = "base64")]`.
+#[cfg(feature = "base64")]
+pub fn __serialize_base64_into<T: AsRef<[u8]>>(v: &T, buf: &mut String) {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode_string(v, buf)
+}
+
+/// Decode the base64 string. The whitespaces are ignored since the long values are usually
+/// wrapped into lines.
+///
+/// It is used by the fields with `#[xmlserde(encode = "base64")]`.
+#[cfg(feature = "base64")]
+pub fn __deserialize_base64(s: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    let s = s
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| e.to_string())
+}
+
+/// Serialize the values joined by `sep` and append them to `buf`.
+///
+/// It is used by the `attr` fields with `#[xmlserde(sep = " ")]`.
+pub fn __serialize_list_into<T: XmlValue>(values: &[T], sep: &str, buf: &mut String) {
+    values.iter().enumerate().for_each(|(i, v)| {
+        if i > 0 {
+            buf.push_str(sep);
+        }
+        v.serialize_into(buf);
+    });
+}
+
+/// Split `s` by `sep` and deserialize each item. A whitespace `sep` splits the string by
+/// any number of whitespaces.
+///
+/// It is used by the `attr` fields with `#[xmlserde(sep = " ")]`.
+pub fn __deserialize_list<T: XmlValue>(s: &str, sep: &str) -> Result<Vec<T>, String> {
+    if sep.trim().is_empty() {
+        s.split_whitespace().map(T::deserialize).collect()
+    } else if s.is_empty() {
+        Ok(vec![])
+    } else {
+        s.split(sep).map(T::deserialize).collect()
+    }
+}
+
+/// Serialize the entries of `map` like `a:1;b:2` in the order of their keys and append them to `buf`.
+///
+/// It is used by the `attr` fields with `#[xmlserde(map_sep = ";", kv_sep = ":")]`.
+pub fn __serialize_map_into<'a, M>(map: &'a M, map_sep: &str, kv_sep: &str, buf: &mut String)
+where
+    &'a M: IntoIterator<Item = (&'a String, &'a String)>,
+{
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries.into_iter().enumerate().for_each(|(i, (k, v))| {
+        if i > 0 {
+            buf.push_str(map_sep);
+        }
+        buf.push_str(k);
+        buf.push_str(kv_sep);
+        buf.push_str(v);
+    });
+}
+
+/// Split `s` into the pairs by `map_sep` and each pair into the key and the value by `kv_sep`.
+/// The whitespaces around the keys and the values are trimmed and the empty pairs are skipped.
+///
+/// It is used by the `attr` fields with `#[xmlserde(map_sep = ";", kv_sep = ":")]`.
+pub fn __deserialize_map<M: Default + Extend<(String, String)>>(
+    s: &str,
+    map_sep: &str,
+    kv_sep: &str,
+) -> Result<M, String> {
+    let mut map = M::default();
+    for pair in s.split(map_sep) {
+        if pair.trim().is_empty() {
+            continue;
+        }
+        match pair.split_once(kv_sep) {
+            Some((k, v)) => map.extend(std::iter::once((
+                k.trim().to_string(),
+                v.trim().to_string(),
+            ))),
+            None => return Err(format!("missing `{}` in the pair `{}`", kv_sep, pair)),
+        }
+    }
+    Ok(map)
+}
+
+/// Like `__deserialize_list` but requires exactly `N` items.
+pub fn __deserialize_array<T: XmlValue, const N: usize>(
+    s: &str,
+    sep: &str,
+) -> Result<[T; N], String> {
+    use std::convert::TryFrom;
+    let list = __deserialize_list::<T>(s, sep)?;
+    let len = list.len();
+    <[T; N]>::try_from(list).map_err(|_| format!("expect {} items but found {} in {:?}", N, len, s))
+}
+
+/// Split `s` by `sep` into exactly `N` items. A whitespace `sep` splits the string by
+/// any number of whitespaces.
+///
+/// It is used by the tuple `attr` fields with `#[xmlserde(sep = "-")]`.
+pub fn __split_tuple<'a, const N: usize>(s: &'a str, sep: &str) -> Result<[&'a str; N], String> {
+    use std::convert::TryFrom;
+    let items = if sep.trim().is_empty() {
+        s.split_whitespace().collect::<Vec<_>>()
+    } else {
+        s.split(sep).collect::<Vec<_>>()
+    };
+    let len = items.len();
+    <[&str; N]>::try_from(items)
+        .map_err(|_| format!("expect {} items but found {} in {:?}", N, len, s))
+}
+
+/// A literal value in `xml_serde_enum!`, either a string or an integer.
+#[doc(hidden)]
+pub trait __EnumLiteral {
+    fn __to_str(self) -> std::borrow::Cow<'static, str>;
+}
+
+impl __EnumLiteral for &'static str {
+    fn __to_str(self) -> std::borrow::Cow<'static, str> {
+        std::borrow::Cow::Borrowed(self)
+    }
+}
+
+macro_rules! impl_enum_literal_for_int {
+    ($($t:ty),*) => {
+        $(impl __EnumLiteral for $t {
+            fn __to_str(self) -> std::borrow::Cow<'static, str> {
+                std::borrow::Cow::Owned(self.to_string())
+            }
+        })*
+    };
+}
+
+impl_enum_literal_for_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// The string form of a literal value in `xml_serde_enum!`.
+///
+/// It is used by the `xml_serde_enum!` macro.
+pub fn __enum_literal<T: __EnumLiteral>(v: T) -> std::borrow::Cow<'static, str> {
+    v.__to_str()
+}
+
+/// Whether `s` is the string form of a literal value in `xml_serde_enum!`, ignoring the ASCII case
+/// when `ci` is true.
+///
+/// It is used by the `xml_serde_enum!` macro.
+pub fn __enum_literal_eq<T: __EnumLiteral>(s: &str, v: T, ci: bool) -> bool {
+    let v = v.__to_str();
+    if ci {
+        s.eq_ignore_ascii_case(&v)
+    } else {
+        s == v
+    }
+}
+
+thread_local! {
+    // The read buffers given back by the finished `deserialize` calls, which are
+    // reused by the next ones instead of allocating new buffers.
+    static READ_BUFS: std::cell::RefCell<Vec<Vec<u8>>> = const { std::cell::RefCell::new(Vec::new()) };
+    // The capacity of the new read buffers set by `DeOptions::buffer_capacity`.
+    static BUF_CAPACITY: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn buf_capacity() -> usize {
+    BUF_CAPACITY.try_with(|c| c.get()).unwrap_or(0)
+}
+
+/// A read buffer taken from a thread local pool by `__take_buf`.
+/// It is cleared and given back to the pool when dropped.
+#[doc(hidden)]
+pub struct __ReadBuf(Vec<u8>);
+
+impl std::ops::Deref for __ReadBuf {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for __ReadBuf {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl Drop for __ReadBuf {
+    fn drop(&mut self) {
+        // Don't keep the buffers grown by a huge element.
+        if self.0.capacity() > buf_capacity().max(1 << 16) {
+            return;
+        }
+        let mut buf = std::mem::take(&mut self.0);
+        buf.clear();
+        let _ = READ_BUFS.try_with(|bufs| bufs.borrow_mut().push(buf));
+    }
+}
+
+/// Take a read buffer from the pool, so that the nested `deserialize` calls reuse
+/// the buffers of the finished ones rather than allocating their own.
+pub fn __take_buf() -> __ReadBuf {
+    let mut buf = READ_BUFS
+        .try_with(|bufs| bufs.borrow_mut().pop())
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    buf.reserve(buf_capacity());
+    __ReadBuf(buf)
+}
+
+thread_local! {
+    // The namespace bindings declared by the elements being deserialized,
+    // from the outermost to the innermost.
+    static NS_SCOPE: std::cell::RefCell<Vec<(Vec<u8>, Vec<u8>)>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Record the namespace bindings like `xmlns:r="..."` declared in `attrs` and return the number of them.
+/// The default namespace `xmlns="..."` is recorded with an empty prefix and `xml:space` is recorded as well.
+/// They are visible to `__attr_matches_ns` until the returned guard is dropped.
+///
+/// It is called at the start of deserializing a struct.
+pub fn __push_ns_scope(attrs: quick_xml::events::attributes::Attributes) -> __NsScope {
+    let mut cnt = 0;
+    attrs.flatten().for_each(|attr| {
+        let key = attr.key.into_inner();
+        // `xml:space` is recorded with its name, which can't be a prefix.
+        let prefix = if key == XML_SPACE {
+            Some(key)
+        } else {
+            ns_decl_prefix(key)
+        };
+        if let Some(prefix) = prefix {
+            let ns = ns_decl_value(&attr);
+            NS_SCOPE.with(|s| s.borrow_mut().push((prefix.to_vec(), ns)));
+            cnt += 1;
+        }
+    });
+    __NsScope(cnt)
+}
+
+/// The namespace bindings recorded by `__push_ns_scope`.
+/// They are dropped with it, even if the struct returns early with an error.
+#[doc(hidden)]
+pub struct __NsScope(usize);
+
+impl Drop for __NsScope {
+    fn drop(&mut self) {
+        NS_SCOPE.with(|s| {
+            let mut s = s.borrow_mut();
+            let len = s.len().saturating_sub(self.0);
+            s.truncate(len);
+        });
+    }
+}
+
+const XML_SPACE: &[u8] = b"xml:space";
+
+/// Check if the whitespaces should be kept, which means that `xml:space="preserve"` is declared
+/// in `attrs` or, if not, by the innermost ancestor declaring `xml:space`.
+///
+/// It is used by the `text` fields and the untagged enums with `#[xmlserde(trim)]`.
+pub fn __preserve_space(attrs: quick_xml::events::attributes::Attributes) -> bool {
+    let own = attrs
+        .flatten()
+        .find(|a| a.key.into_inner() == XML_SPACE)
+        .map(|a| ns_decl_value(&a));
+    match own {
+        Some(v) => v == b"preserve",
+        None => NS_SCOPE.with(|s| {
+            s.borrow()
+                .iter()
+                .rev()
+                .find(|(p, _)| p == XML_SPACE)
+                .is_some_and(|(_, v)| v == b"preserve")
+        }),
+    }
+}
+
+/// Get the prefix declared by the attribute `key` if it is `xmlns` or `xmlns:prefix`.
+fn ns_decl_prefix(key: &[u8]) -> Option<&[u8]> {
+    match key.strip_prefix(b"xmlns") {
+        Some(b"") => Some(&b""[..]),
+        Some(p) => p.strip_prefix(b":"),
+        None => None,
+    }
+}
+
+fn ns_decl_value(attr: &quick_xml::events::attributes::Attribute) -> Vec<u8> {
+    attr.unescape_value()
+        .map(|v| v.into_owned().into_bytes())
+        .unwrap_or_else(|_| attr.value.to_vec())
+}
+
+fn clear_ns_scope() {
+    NS_SCOPE.with(|s| s.borrow_mut().clear());
+}
+
+/// Deserialize the captured element and return `None` if it fails.
+///
+/// It is used by the `child` fields with `#[xmlserde(skip_errors)]`.
+pub fn __try_deserialize_unparsed<T: XmlDeserialize>(u: Unparsed) -> Option<T> {
+    u.deserialize_to::<T>().ok()
+}
+
+/// Get the namespace bound to `prefix` in the current scope.
+pub fn __resolve_ns_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    if prefix == b"xml" {
+        return Some(b"http://www.w3.org/XML/1998/namespace".to_vec());
+    }
+    NS_SCOPE.with(|s| {
+        s.borrow()
+            .iter()
+            .rev()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, ns)| ns.clone())
+    })
+}
+
+/// Get the namespace of the element `tag` from its prefix, or the default namespace if it has no prefix.
+///
+/// It is used by the `tag_ns` fields.
+pub fn __element_ns(tag: &[u8]) -> Option<String> {
+    let prefix = match tag.iter().position(|c| *c == b':') {
+        Some(i) => &tag[..i],
+        None => b"",
+    };
+    __resolve_ns_prefix(prefix)
+        .filter(|ns| !ns.is_empty())
+        .map(|ns| String::from_utf8_lossy(&ns).into_owned())
+}
+
+/// Check if the attribute `key` is `local` in the namespace `ns`, whatever its prefix is.
+/// Attributes without a prefix are in no namespace. `ci` compares the names ignoring ASCII case.
+///
+/// It is used by the `attr` fields with `#[xmlserde(ns = b"...")]`.
+pub fn __attr_matches_ns(key: &[u8], local: &[u8], ns: &[u8], ci: bool) -> bool {
+    let local = quick_xml::name::QName(local).local_name().into_inner();
+    match key.iter().position(|c| *c == b':') {
+        Some(i) => {
+            names_eq(&key[i + 1..], local, ci)
+                && __resolve_ns_prefix(&key[..i]).as_deref() == Some(ns)
+        }
+        None => false,
+    }
+}
+
+/// Check if the element `tag` is `local` in the namespace `ns`, whatever its prefix is.
+/// Elements without a prefix are in the default namespace. The prefix is resolved against the
+/// `xmlns` attributes of the element itself before the ones in scope. `ci` compares the names ignoring ASCII case.
+///
+/// It is used by the `child` and `sfc` fields with `#[xmlserde(ns = b"...")]`.
+pub fn __element_matches_ns(
+    tag: &[u8],
+    local: &[u8],
+    ns: &[u8],
+    attrs: quick_xml::events::attributes::Attributes,
+    ci: bool,
+) -> bool {
+    let local = quick_xml::name::QName(local).local_name().into_inner();
+    let (prefix, name) = match tag.iter().position(|c| *c == b':') {
+        Some(i) => (&tag[..i], &tag[i + 1..]),
+        None => (b"".as_ref(), tag),
+    };
+    if !names_eq(name, local, ci) {
+        return false;
+    }
+    let declared = attrs
+        .flatten()
+        .find(|a| ns_decl_prefix(a.key.into_inner()) == Some(prefix))
+        .map(|a| ns_decl_value(&a));
+    match declared {
+        Some(declared) => declared == ns,
+        None => __resolve_ns_prefix(prefix).as_deref() == Some(ns),
+    }
+}
+
+fn names_eq(a: &[u8], b: &[u8], ci: bool) -> bool {
+    if ci {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Append the attribute to `rest` like `key="value"`, separated by a space, unless its key is in `declared`.
+/// The value is kept escaped as it is in the document.
+///
+/// It is used by the `rest_attrs_joined` fields.
+pub fn __push_rest_attr(
+    rest: &mut String,
+    attr: &quick_xml::events::attributes::Attribute,
+    declared: &[&[u8]],
+) {
+    let key = attr.key.into_inner();
+    if declared.contains(&key) {
+        return;
+    }
+    if !rest.is_empty() {
+        rest.push(' ');
+    }
+    rest.push_str(&String::from_utf8_lossy(key));
+    rest.push_str("=\"");
+    // The value may be quoted by `'` in the document.
+    rest.push_str(&String::from_utf8_lossy(&attr.value).replace('"', "&quot;"));
+    rest.push('"');
+}
+
+/// Insert the attribute into `map` with its unescaped value, unless its key is in `declared`.
+///
+/// It is used by the `attr_map` fields.
+pub fn __push_attr_map<M: Extend<(String, String)>>(
+    map: &mut M,
+    attr: &quick_xml::events::attributes::Attribute,
+    declared: &[&[u8]],
+) {
+    let key = attr.key.into_inner();
+    if declared.contains(&key) {
+        return;
+    }
+    let key = String::from_utf8_lossy(key).into_owned();
+    map.extend(std::iter::once((key, __attr_value(attr).into_owned())));
+}
+
+/// Push the entries of `map` to `start` in the order of their keys, so that the output
+/// does not depend on the order of a `HashMap`.
+///
+/// It is used by the `attr_map` fields.
+pub fn __push_attr_map_attrs<'a, M>(start: &mut quick_xml::events::BytesStart, map: &'a M)
+where
+    &'a M: IntoIterator<Item = (&'a String, &'a String)>,
+{
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries.into_iter().for_each(|(k, v)| {
+        start.push_attribute((k.as_str(), v.as_str()));
+    });
+}
+
+/// Get the local name of a tag without its prefix, like `item` of `a:item`.
+///
+/// It is used by the fields with `#[xmlserde(ignore_prefix)]`.
+pub fn __local_name(name: &[u8]) -> &[u8] {
+    quick_xml::name::QName(name).local_name().into_inner()
+}
+
+/// Push an attribute without a value like `selected` in `<option selected>` to `start`.
+/// It is not well-formed XML and only meant for the HTML-like outputs.
+///
+/// It is used by the `attr` fields with `#[xmlserde(bare_bool)]`.
+pub fn __push_bare_attr(start: &mut quick_xml::events::BytesStart, name: &[u8]) {
+    let name_len = start.name().into_inner().len();
+    let mut content = start.to_vec();
+    content.push(b' ');
+    content.extend_from_slice(name);
+    let content = String::from_utf8_lossy(&content).into_owned();
+    *start = quick_xml::events::BytesStart::from_content(content, name_len);
+}
+
+/// Deserialize the value of a `bare_bool` attribute. An empty value or the name of the
+/// attribute itself, like `selected="selected"`, is `true`.
+pub fn __deserialize_bare_bool(s: &str, name: &[u8]) -> Result<bool, String> {
+    if s.is_empty() || s.as_bytes() == name {
+        Ok(true)
+    } else {
+        bool::deserialize(s)
+    }
+}
+
+/// Sort the attributes of `start` by the positions of their names in `order`.
+/// The attributes not in `order` are put after the others in their original order.
+///
+/// It is used by the `attr_order` fields.
+pub fn __reorder_attrs(start: &mut quick_xml::events::BytesStart, order: &[String]) {
+    if order.is_empty() {
+        return;
+    }
+    let mut attrs = start
+        .attributes()
+        .flatten()
+        .map(|attr| (attr.key.into_inner().to_vec(), attr.value.into_owned()))
+        .collect::<Vec<_>>();
+    attrs.sort_by_key(|(key, _)| {
+        order
+            .iter()
+            .position(|n| n.as_bytes() == key.as_slice())
+            .unwrap_or(order.len())
+    });
+    start.clear_attributes();
+    attrs.iter().for_each(|(key, value)| {
+        start.push_attribute(quick_xml::events::attributes::Attribute {
+            key: quick_xml::name::QName(key),
+            value: std::borrow::Cow::Borrowed(value),
+        });
+    });
+}
+
+/// Push the attributes joined in `rest` by `__push_rest_attr` to `start`.
+///
+/// It is used by the `rest_attrs_joined` fields.
+pub fn __push_rest_attrs(start: &mut quick_xml::events::BytesStart, rest: &str) {
+    quick_xml::events::attributes::Attributes::new(rest, 0)
+        .flatten()
+        .for_each(|attr| start.push_attribute(attr));
+}
+
+/// Numbers that can be serialized in the lexical form of XSD `decimal`, which has no exponent
+/// and no trailing zeros in the fraction, like `1.5`, `-0.25` and `100`.
+///
+/// It is used by the fields with `#[xmlserde(xsd = "decimal")]`.
+pub trait XsdDecimal {
+    fn serialize_decimal(&self) -> String;
+}
+
+macro_rules! impl_xsd_decimal_for_int {
+    ($num:ty) => {
+        impl XsdDecimal for $num {
+            fn serialize_decimal(&self) -> String {
+                self.to_string()
+            }
+        }
+    };
+}
+
+macro_rules! impl_xsd_decimal_for_float {
+    ($num:ty) => {
+        impl XsdDecimal for $num {
+            fn serialize_decimal(&self) -> String {
+                // `Display` of floats never uses the exponent and prints the shortest digits.
+                // The only thing left is that negative zero is not a canonical decimal.
+                if *self == 0.0 {
+                    String::from("0")
+                } else {
+                    self.to_string()
+                }
+            }
+        }
+    };
+}
+
+impl_xsd_decimal_for_int!(i8);
+impl_xsd_decimal_for_int!(u8);
+impl_xsd_decimal_for_int!(i16);
+impl_xsd_decimal_for_int!(u16);
+impl_xsd_decimal_for_int!(i32);
+impl_xsd_decimal_for_int!(u32);
+impl_xsd_decimal_for_int!(i64);
+impl_xsd_decimal_for_int!(u64);
+impl_xsd_decimal_for_int!(i128);
+impl_xsd_decimal_for_int!(u128);
+impl_xsd_decimal_for_int!(isize);
+impl_xsd_decimal_for_int!(usize);
+impl_xsd_decimal_for_float!(f32);
+impl_xsd_decimal_for_float!(f64);
@@ -0,0 +1,205 @@
+use serde::ser::{Impossible, Serialize, Serializer};
+
+use crate::XmlValue;
+
+/// A wrapper making a leaf value implementing serde's `Serialize` and `Deserialize` usable
+/// as an `attr` or a `text`.
+///
+/// Only the scalar values are supported, like numbers, strings, booleans, unit variants and the
+/// newtypes of them. Deserializing is done by `quick_xml::de::SimpleTypeDeserializer`.
+///
+/// ```ignore
+/// #[derive(XmlSerialize, XmlDeserialize)]
+/// pub struct Cell {
+///     #[xmlserde(name = b"kind", ty = "attr")]
+///     pub kind: SerdeAttr<CellKind>, // CellKind derives serde's traits
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SerdeAttr<T>(pub T);
+
+impl<T> XmlValue for SerdeAttr<T>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    fn serialize(&self) -> String {
+        let mut buf = String::new();
+        self.serialize_into(&mut buf);
+        buf
+    }
+
+    fn serialize_into(&self, buf: &mut String) {
+        if let Err(e) = self.0.serialize(ScalarSerializer { buf }) {
+            panic!("failed to serialize by serde: {}", e)
+        }
+    }
+
+    fn deserialize(s: &str) -> Result<Self, String> {
+        let de = quick_xml::de::SimpleTypeDeserializer::from_text(std::borrow::Cow::Borrowed(s));
+        T::deserialize(de).map(SerdeAttr).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+fn unsupported(ty: &str) -> Error {
+    Error(format!("{} is not a scalar value", ty))
+}
+
+/// Write the scalar value into `buf`.
+struct ScalarSerializer<'a> {
+    buf: &'a mut String,
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), Error> {
+                use std::fmt::Write;
+                let _ = write!(self.buf, "{}", v);
+                Ok(())
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for ScalarSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_display! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Error> {
+        Err(unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.buf.push_str(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported(name))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("sequence"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported(name))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported(name))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported(name))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported(name))
+    }
+}
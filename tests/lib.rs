@@ -1,7 +1,18 @@
 #[cfg(test)]
+#[allow(
+    dead_code,
+    clippy::bool_assert_comparison,
+    clippy::get_first,
+    clippy::needless_borrow,
+    clippy::redundant_pattern_matching
+)]
 mod tests {
 
-    use xmlserde::{xml_deserialize_from_str, xml_serialize, Unparsed, XmlValue};
+    use xmlserde::{
+        xml_deserialize_from_str, xml_serialize, xml_serialize_pretty, xml_serialize_to_bytes,
+        xml_serialize_untagged, xml_serialize_with_decl, xml_serialize_with_decl_config,
+        DeclConfig, Unparsed, Verbatim, XmlDeError, XmlDeErrorAt, XmlSpace, XmlValue,
+    };
     use xmlserde::{xml_serde_enum, XmlDeserialize, XmlSerialize};
     use xmlserde_derives::{XmlDeserialize, XmlSerialize};
 
@@ -45,9 +56,9 @@ mod tests {
                 reader: &mut xmlserde::quick_xml::Reader<B>,
                 attrs: xmlserde::quick_xml::events::attributes::Attributes,
                 is_empty: bool,
-            ) -> Self {
-                let inner = InnerProperties::deserialize(tag, reader, attrs, is_empty);
-                Self(inner.properties)
+            ) -> Result<Self, XmlDeError> {
+                let inner = InnerProperties::deserialize(tag, reader, attrs, is_empty)?;
+                Ok(Self(inner.properties))
             }
         }
 
@@ -852,4 +863,3549 @@ mod tests {
         #[derive(Debug, XmlDeserialize, XmlSerialize)]
         pub struct CtTextParagraphProperties {}
     }
+
+    #[test]
+    fn test_enum_on_unknown_skip() {
+        #[derive(XmlDeserialize)]
+        pub struct A {}
+
+        #[derive(XmlDeserialize)]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+        }
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            content: Vec<Content>,
+            #[xmlserde(name = b"e", ty = "child")]
+            e: Content,
+        }
+
+        let xml = r#"<root><a/><b/><a/><e><b/><a/></e></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(root.content.len(), 2);
+        assert!(matches!(root.e, Content::A(_)));
+    }
+
+    #[test]
+    fn test_enum_on_unknown_error_in_untag() {
+        #[derive(XmlDeserialize)]
+        pub struct A {}
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(on_unknown = "error")]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+        }
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            _content: Vec<Content>,
+        }
+
+        let xml = r#"<root><a/><b/></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::UnknownField { field }) if field == "b"));
+    }
+
+    #[test]
+    fn test_enum_on_unknown_error_in_child() {
+        #[derive(XmlDeserialize)]
+        pub struct A {}
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(on_unknown = "error")]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+        }
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(name = b"e", ty = "child")]
+            _e: Content,
+        }
+
+        let xml = r#"<root><e><b/><a/></e></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::UnknownField { field }) if field == "b"));
+    }
+
+    #[test]
+    fn test_enum_on_unknown_capture() {
+        #[derive(XmlDeserialize, XmlSerialize)]
+        pub struct A {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: u8,
+        }
+
+        #[derive(XmlDeserialize, XmlSerialize)]
+        #[xmlserde(on_unknown = "capture")]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+            #[xmlserde(ty = "any")]
+            Other(Unparsed),
+        }
+
+        #[derive(XmlDeserialize, XmlSerialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            content: Vec<Content>,
+            #[xmlserde(name = b"e", ty = "child")]
+            e: Content,
+        }
+
+        let xml = r#"<root><e><d/></e><a v="1"/><b x="2"><c/></b><a v="3"/></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(root.content.len(), 3);
+        assert!(matches!(&root.content[1], Content::Other(u) if u.tag() == "b"));
+        assert!(matches!(&root.e, Content::Other(u) if u.tag() == "d"));
+        assert_eq!(xml_serialize(root), xml);
+    }
+
+    #[test]
+    fn serialize_xsd_decimal() {
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"price")]
+        struct Price {
+            #[xmlserde(name = b"discount", ty = "attr", xsd = "decimal")]
+            discount: Option<f32>,
+            #[xmlserde(name = b"tax", ty = "attr", xsd = "decimal")]
+            tax: f64,
+            #[xmlserde(ty = "text", xsd = "decimal")]
+            value: f64,
+        }
+
+        let p = Price {
+            discount: Some(0.25),
+            tax: -0.0,
+            value: 1.50,
+        };
+        assert_eq!(
+            xml_serialize(p),
+            r#"<price discount="0.25" tax="0">1.5</price>"#
+        );
+
+        let p = Price {
+            discount: None,
+            tax: 1e21,
+            value: 100.0,
+        };
+        assert_eq!(
+            xml_serialize(p),
+            r#"<price tax="1000000000000000000000">100</price>"#
+        );
+    }
+
+    #[test]
+    fn serialize_to_bytes() {
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"Person")]
+        struct Person {
+            #[xmlserde(name = b"age", ty = "attr")]
+            age: u16,
+            #[xmlserde(ty = "text")]
+            name: String,
+        }
+        let result = xml_serialize_to_bytes(Person {
+            age: 12,
+            name: String::from("Tom"),
+        });
+        assert_eq!(result, b"<Person age=\"12\">Tom</Person>");
+    }
+
+    #[test]
+    fn test_untag_enum_with_attrs_on_wrapper() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct IntValue {
+            #[xmlserde(ty = "text")]
+            value: i32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct StrValue {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        enum Value {
+            #[xmlserde(name = b"int")]
+            Int(IntValue),
+            #[xmlserde(name = b"str")]
+            Str(StrValue),
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Param {
+            #[xmlserde(name = b"required", ty = "attr")]
+            required: bool,
+            #[xmlserde(ty = "untag")]
+            value: Value,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"params")]
+        struct Params {
+            #[xmlserde(name = b"param", ty = "child")]
+            params: Vec<Param>,
+        }
+
+        let xml = r#"<params><param required="1"><int>5</int></param><param required="0"><str>s</str></param></params>"#;
+        let result = xml_deserialize_from_str::<Params>(xml).unwrap();
+        assert_eq!(result.params.len(), 2);
+        let first = &result.params[0];
+        assert!(first.required);
+        match &first.value {
+            Value::Int(i) => assert_eq!(i.value, 5),
+            Value::Str(_) => panic!(),
+        }
+        let second = &result.params[1];
+        assert!(!second.required);
+        match &second.value {
+            Value::Str(s) => assert_eq!(s.value, "s"),
+            Value::Int(_) => panic!(),
+        }
+        assert_eq!(xml_serialize(result), xml);
+    }
+
+    #[test]
+    fn test_transparent_enum_variant() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Circle {
+            #[xmlserde(name = b"r", ty = "attr")]
+            r: u32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Rect {
+            #[xmlserde(name = b"w", ty = "attr")]
+            w: u32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Shape {
+            #[xmlserde(name = b"circle", ty = "child")]
+            circle: Option<Circle>,
+            #[xmlserde(name = b"rect", ty = "child")]
+            rect: Option<Rect>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Text {
+            #[xmlserde(ty = "text")]
+            t: String,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        enum Item {
+            #[xmlserde(name = b"text")]
+            Text(Text),
+            #[xmlserde(name = b"")]
+            Shape(Shape),
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            items: Vec<Item>,
+            #[xmlserde(name = b"last", ty = "child")]
+            last: Item,
+        }
+
+        let xml =
+            r#"<root><last><rect w="3"/></last><text>a</text><circle r="1"/><rect w="2"/></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(root.items.len(), 3);
+        assert!(matches!(&root.items[0], Item::Text(t) if t.t == "a"));
+        match &root.items[1] {
+            Item::Shape(s) => {
+                assert_eq!(s.circle.as_ref().unwrap().r, 1);
+                assert!(s.rect.is_none());
+            }
+            Item::Text(_) => panic!(),
+        }
+        match &root.items[2] {
+            Item::Shape(s) => assert_eq!(s.rect.as_ref().unwrap().w, 2),
+            Item::Text(_) => panic!(),
+        }
+        match &root.last {
+            Item::Shape(s) => assert_eq!(s.rect.as_ref().unwrap().w, 3),
+            Item::Text(_) => panic!(),
+        }
+        assert_eq!(xml_serialize(root), xml);
+    }
+
+    #[test]
+    fn test_attr_list_and_array() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"shape")]
+        struct Shape {
+            #[xmlserde(name = b"point", ty = "attr", sep = " ")]
+            point: [f64; 2],
+            #[xmlserde(name = b"tags", ty = "attr", sep = ",")]
+            tags: Vec<String>,
+        }
+
+        let xml = r#"<shape point="1.5  2" tags="a,b"/>"#;
+        let shape = xml_deserialize_from_str::<Shape>(xml).unwrap();
+        assert_eq!(shape.point, [1.5, 2.0]);
+        assert_eq!(shape.tags, vec!["a", "b"]);
+        assert_eq!(xml_serialize(shape), r#"<shape point="1.5 2" tags="a,b"/>"#);
+
+        let xml = r#"<shape point="1 2"/>"#;
+        let shape = xml_deserialize_from_str::<Shape>(xml).unwrap();
+        assert!(shape.tags.is_empty());
+        assert_eq!(xml_serialize(shape), xml);
+
+        let r = xmlserde::__deserialize_array::<f64, 2>("1 2 3", " ");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn test_attr_array_wrong_count() {
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"shape")]
+        struct Shape {
+            #[xmlserde(name = b"point", ty = "attr", sep = " ")]
+            _point: [f64; 2],
+        }
+
+        let xml = r#"<shape point="1.5"/>"#;
+        let r = xml_deserialize_from_str::<Shape>(xml).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { .. })));
+    }
+
+    #[test]
+    fn serialize_with_decl_config() {
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"doc")]
+        struct Doc {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u16,
+        }
+
+        let result = xml_serialize_with_decl(Doc { id: 1 });
+        assert_eq!(
+            result,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><doc id="1"/>"#
+        );
+
+        let config = DeclConfig {
+            standalone: None,
+            ..Default::default()
+        };
+        let result = xml_serialize_with_decl_config(Doc { id: 1 }, &config);
+        assert_eq!(
+            result,
+            r#"<?xml version="1.0" encoding="UTF-8"?><doc id="1"/>"#
+        );
+
+        let config = DeclConfig {
+            version: "1.1",
+            encoding: None,
+            standalone: None,
+        };
+        let result = xml_serialize_with_decl_config(Doc { id: 1 }, &config);
+        assert_eq!(result, r#"<?xml version="1.1"?><doc id="1"/>"#);
+    }
+
+    #[test]
+    fn test_nested_vec_by_structs() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Cell {
+            #[xmlserde(ty = "text")]
+            v: u32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Row {
+            #[xmlserde(name = b"cell", ty = "child")]
+            cells: Vec<Cell>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"table")]
+        struct Table {
+            #[xmlserde(name = b"row", ty = "child")]
+            rows: Vec<Row>,
+        }
+
+        let xml = r#"<table><row><cell>1</cell><cell>2</cell></row><row/><row><cell>3</cell></row></table>"#;
+        let table = xml_deserialize_from_str::<Table>(xml).unwrap();
+        let values = table
+            .rows
+            .iter()
+            .map(|r| r.cells.iter().map(|c| c.v).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![vec![1, 2], vec![], vec![3]]);
+        assert_eq!(xml_serialize(table), xml);
+    }
+
+    #[test]
+    fn test_enum_on_missing_default() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct A {}
+
+        #[derive(Debug, Default, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(on_missing = "default")]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+            #[default]
+            #[xmlserde(name = b"none")]
+            Nothing,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            content: Content,
+            #[xmlserde(name = b"e", ty = "child")]
+            e: Content,
+        }
+
+        let xml = r#"<root><e/><a/></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert!(matches!(root.content, Content::A(_)));
+        assert!(matches!(root.e, Content::Nothing));
+
+        let xml = r#"<root><e/></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert!(matches!(root.content, Content::Nothing));
+        assert!(matches!(root.e, Content::Nothing));
+
+        let xml = r#"<root><a/><e><a/></e></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert!(matches!(root.content, Content::A(_)));
+        assert!(matches!(root.e, Content::A(_)));
+    }
+
+    #[test]
+    fn test_enum_on_missing_error() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct A {}
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(on_missing = "error")]
+        enum Content {
+            #[xmlserde(name = b"a")]
+            A(A),
+        }
+
+        // `panic` is still accepted as the deprecated name of `error`.
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(on_missing = "panic")]
+        enum Legacy {
+            #[xmlserde(name = b"a")]
+            A(A),
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(name = b"c", ty = "child")]
+            current: Content,
+            #[xmlserde(name = b"l", ty = "child")]
+            legacy: Legacy,
+        }
+
+        let xml = r#"<root><c><a/></c><l><a/></l></root>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert!(matches!(root.current, Content::A(_)));
+        assert!(matches!(root.legacy, Legacy::A(_)));
+
+        let xml = r#"<root><c/><l><a/></l></root>"#;
+        let err = xml_deserialize_from_str::<Root>(xml).unwrap_err();
+        assert!(matches!(
+            err.into_inner(),
+            XmlDeError::MissingVariant { .. }
+        ));
+
+        let xml = r#"<root><c><a/></c><l/></root>"#;
+        let err = xml_deserialize_from_str::<Root>(xml).unwrap_err();
+        assert!(matches!(
+            err.into_inner(),
+            XmlDeError::MissingVariant { .. }
+        ));
+    }
+
+    #[test]
+    fn test_xml_space_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"t")]
+        struct T {
+            #[xmlserde(name = b"xml:space", ty = "attr")]
+            space: Option<XmlSpace>,
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+
+        let xml = r#"<t xml:space="preserve">  a </t>"#;
+        let t = xml_deserialize_from_str::<T>(xml).unwrap();
+        assert_eq!(t.space, Some(XmlSpace::Preserve));
+        assert_eq!(t.text, "  a ");
+        assert_eq!(xml_serialize(t), xml);
+
+        let xml = r#"<t>a</t>"#;
+        let t = xml_deserialize_from_str::<T>(xml).unwrap();
+        assert_eq!(t.space.unwrap_or_default(), XmlSpace::Default);
+    }
+
+    #[test]
+    fn test_case_insensitive_names() {
+        #[derive(XmlDeserialize, Default)]
+        #[xmlserde(case_insensitive_names)]
+        pub struct Name {
+            #[xmlserde(name = b"en", ty = "attr")]
+            pub en: String,
+        }
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"person", case_insensitive_names)]
+        pub struct Person {
+            #[xmlserde(name = b"age", ty = "attr")]
+            pub age: u16,
+            #[xmlserde(name = b"name", ty = "child")]
+            pub name: Name,
+            #[xmlserde(name = b"lefty", ty = "sfc")]
+            pub lefty: bool,
+            #[xmlserde(name = b"pet", ty = "child")]
+            pub pets: Vec<Name>,
+        }
+
+        let xml = r#"<Person AGE="16"><Name EN="Tom"></Name><Lefty/><pet En="a"/><PET en="b"></PET></Person>"#;
+        let p = xml_deserialize_from_str::<Person>(xml).unwrap();
+        assert_eq!(p.age, 16);
+        assert_eq!(p.name.en, "Tom");
+        assert!(p.lefty);
+        assert_eq!(p.pets.len(), 2);
+        assert_eq!(p.pets[1].en, "b");
+    }
+
+    #[test]
+    fn test_untag_enum_vec_preserve_whitespace() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"p")]
+        pub struct P {
+            #[xmlserde(name = b"id", ty = "attr")]
+            pub id: u8,
+            #[xmlserde(ty = "untag", preserve_whitespace)]
+            pub content: Vec<Content>,
+        }
+
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub enum Content {
+            #[xmlserde(ty = "text")]
+            Text(String),
+            #[xmlserde(name = b"b", ty = "child")]
+            Bold(Bold),
+        }
+
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub struct Bold {
+            #[xmlserde(ty = "text")]
+            pub t: String,
+        }
+
+        let xml = r#"<p id="1">a <b>b</b> <b>c</b></p>"#;
+        let p = xml_deserialize_from_str::<P>(xml).unwrap();
+        assert_eq!(
+            p.content,
+            vec![
+                Content::Text(String::from("a ")),
+                Content::Bold(Bold {
+                    t: String::from("b")
+                }),
+                Content::Text(String::from(" ")),
+                Content::Bold(Bold {
+                    t: String::from("c")
+                }),
+            ]
+        );
+        assert_eq!(xml_serialize(p), xml);
+    }
+
+    #[test]
+    fn test_bool_capitalized() {
+        assert!(bool::deserialize("True").unwrap());
+        assert!(!bool::deserialize("False").unwrap());
+        assert!(bool::deserialize("TRUE").unwrap());
+        assert!(bool::deserialize("yes").is_err());
+
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"a")]
+        pub struct A {
+            #[xmlserde(name = b"b", ty = "attr")]
+            pub b: bool,
+        }
+        let a = xml_deserialize_from_str::<A>(r#"<a b="True"/>"#).unwrap();
+        assert!(a.b);
+    }
+
+    #[test]
+    fn test_attr_with_ns() {
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(with_custom_ns(
+            b"r",
+            b"http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+        ))]
+        pub struct Sheet {
+            #[xmlserde(name = b"name", ty = "attr")]
+            pub name: String,
+            #[xmlserde(
+                name = b"id",
+                ty = "attr",
+                ns = b"http://schemas.openxmlformats.org/officeDocument/2006/relationships"
+            )]
+            pub id: String,
+        }
+
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"workbook")]
+        pub struct Workbook {
+            #[xmlserde(name = b"sheet", ty = "child")]
+            pub sheets: Vec<Sheet>,
+        }
+
+        let xml = r#"<workbook xmlns:rel="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheet name="a" rel:id="rId1"/><sheet name="b" id="x" r:id="y" rel:id="rId2"/></workbook>"#;
+        let wb = xml_deserialize_from_str::<Workbook>(xml).unwrap();
+        assert_eq!(wb.sheets[0].id, "rId1");
+        assert_eq!(wb.sheets[1].id, "rId2");
+
+        let expect = r#"<workbook><sheet xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" name="a" r:id="rId1"/><sheet xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" name="b" r:id="rId2"/></workbook>"#;
+        assert_eq!(xml_serialize(wb), expect);
+    }
+
+    #[test]
+    fn test_unparsed_text() {
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct Root {
+            #[xmlserde(name = b"raw", ty = "child")]
+            pub raw: Unparsed,
+        }
+
+        let xml =
+            r#"<root><raw a="1">Hello, <b>big <i>&amp;</i></b> <![CDATA[<world>]]>!</raw></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(r.raw.text(), "Hello, big & <world>!");
+        assert_eq!(xml_serialize(r), xml);
+    }
+
+    #[test]
+    fn test_vec_size_from_attr_declared_after_child() {
+        #[derive(XmlDeserialize, Default)]
+        pub struct Child {
+            #[xmlserde(name = b"v", ty = "attr")]
+            pub v: u16,
+        }
+        fn default_zero() -> u32 {
+            0
+        }
+        #[derive(XmlDeserialize, Default)]
+        #[xmlserde(root = b"root")]
+        pub struct Aa {
+            #[xmlserde(name = b"c", ty = "child", vec_size = "cnt")]
+            pub children: Vec<Child>,
+            #[xmlserde(name = b"other", ty = "child")]
+            pub other: Option<Child>,
+            #[xmlserde(name = b"cnt", ty = "attr", default = "default_zero")]
+            pub cnt: u32,
+        }
+        let xml = r#"<root cnt="100"><c v="1"/><other v="3"/><c v="2"/></root>"#;
+        let result = xml_deserialize_from_str::<Aa>(xml).unwrap();
+        assert_eq!(result.cnt, 100);
+        assert_eq!(result.children.len(), 2);
+        assert!(result.children.capacity() >= 100);
+        assert_eq!(result.other.unwrap().v, 3);
+    }
+
+    #[test]
+    fn test_opt_text_of_empty_element() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        pub struct AStruct {
+            #[xmlserde(ty = "text")]
+            pub text: Option<String>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        pub struct BStruct {
+            #[xmlserde(ty = "text")]
+            pub num: Option<u32>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct Root {
+            #[xmlserde(name = b"ttt", ty = "child")]
+            pub ttt: Option<AStruct>,
+            #[xmlserde(name = b"num", ty = "child")]
+            pub num: Option<BStruct>,
+        }
+
+        let r = xml_deserialize_from_str::<Root>(r#"<root><ttt></ttt><num></num></root>"#).unwrap();
+        assert_eq!(r.ttt.as_ref().unwrap().text.as_deref(), Some(""));
+        assert_eq!(r.num.unwrap().num, None);
+        assert_eq!(
+            xml_serialize(Root {
+                ttt: r.ttt,
+                num: None
+            }),
+            "<root><ttt></ttt></root>"
+        );
+
+        let r = xml_deserialize_from_str::<Root>(r#"<root><ttt/></root>"#).unwrap();
+        assert_eq!(r.ttt.unwrap().text.as_deref(), Some(""));
+
+        let r = xml_deserialize_from_str::<Root>(r#"<root><ttt>abc</ttt></root>"#).unwrap();
+        assert_eq!(r.ttt.unwrap().text.as_deref(), Some("abc"));
+
+        let r = xml_deserialize_from_str::<Root>(r#"<root></root>"#).unwrap();
+        assert!(r.ttt.is_none());
+    }
+
+    #[test]
+    fn test_serialize_into() {
+        let mut buf = String::from("a");
+        12u32.serialize_into(&mut buf);
+        (-1.5f64).serialize_into(&mut buf);
+        true.serialize_into(&mut buf);
+        String::from("b").serialize_into(&mut buf);
+        assert_eq!(buf, "a12-1.51b");
+
+        xml_serde_enum! {
+            Kind {
+                A => "a",
+            }
+        }
+        Kind::A.serialize_into(&mut buf);
+        assert_eq!(buf, "a12-1.51ba");
+
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"n")]
+        pub struct N {
+            #[xmlserde(name = b"a", ty = "attr")]
+            pub a: u8,
+            #[xmlserde(name = b"b", ty = "attr")]
+            pub b: Option<f32>,
+            #[xmlserde(ty = "text")]
+            pub t: i64,
+        }
+        let n = N {
+            a: 1,
+            b: Some(2.5),
+            t: -3,
+        };
+        assert_eq!(xml_serialize(n), r#"<n a="1" b="2.5">-3</n>"#);
+    }
+
+    #[test]
+    fn test_serialize_into_integers() {
+        fn check<T: XmlValue + ToString>(v: T) {
+            let mut buf = String::from("x");
+            v.serialize_into(&mut buf);
+            assert_eq!(buf, format!("x{}", v.to_string()));
+        }
+        check(0u8);
+        check(9u8);
+        check(10u8);
+        check(u8::MAX);
+        check(i8::MIN);
+        check(-1i32);
+        check(1_000_000u32);
+        check(i64::MIN);
+        check(u64::MAX);
+        check(u64::MAX as u128 + 1);
+        check(10_000_000_000_000_000_000u128);
+        check(u128::MAX);
+        check(i128::MIN);
+        check(std::num::NonZeroI16::new(-300).unwrap());
+        check(usize::MAX);
+    }
+
+    #[test]
+    fn test_vec_child_skip_errors() {
+        #[derive(Debug, XmlDeserialize)]
+        pub struct Record {
+            #[xmlserde(name = b"id", ty = "attr")]
+            pub id: u32,
+            #[xmlserde(name = b"name", ty = "child")]
+            pub name: Option<Unparsed>,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"records")]
+        pub struct Records {
+            #[xmlserde(name = b"record", ty = "child", skip_errors)]
+            pub records: Vec<Record>,
+            #[xmlserde(name = b"end", ty = "child")]
+            pub end: Option<Record>,
+        }
+
+        let xml = r#"<records><record id="1"/><record id="x"><name>a</name></record><record id="3"><name/></record><end id="4"/></records>"#;
+        let r = xml_deserialize_from_str::<Records>(xml).unwrap();
+        let ids = r.records.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3]);
+        assert!(r.records[1].name.is_some());
+        assert_eq!(r.end.unwrap().id, 4);
+    }
+
+    #[test]
+    fn ns_scope_dropped_after_error() {
+        #[derive(Debug, XmlDeserialize)]
+        pub struct Record {
+            #[xmlserde(name = b"id", ty = "attr")]
+            pub id: u32,
+            #[xmlserde(name = b"name", ty = "child")]
+            pub name: Option<Unparsed>,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"records")]
+        pub struct Records {
+            #[xmlserde(name = b"record", ty = "child", skip_errors)]
+            pub records: Vec<Record>,
+            #[xmlserde(name = b"end", ty = "child", ns = b"urn:a")]
+            pub end: Option<Record>,
+        }
+
+        let xml =
+            r#"<records xmlns:a="urn:a"><record xmlns:a="urn:b" id="x"/><a:end id="4"/></records>"#;
+        let r = xml_deserialize_from_str::<Records>(xml).unwrap();
+        assert!(r.records.is_empty());
+        assert_eq!(r.end.unwrap().id, 4);
+    }
+
+    #[test]
+    fn test_alias() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        pub struct Item {
+            #[xmlserde(name = b"v", ty = "attr", alias = b"val", alias = b"value")]
+            pub v: u8,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct Root {
+            #[xmlserde(name = b"item", ty = "child", alias = b"entry")]
+            pub items: Vec<Item>,
+        }
+
+        let xml = r#"<root><item v="1"/><entry val="2"></entry><item value="3"></item></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).unwrap();
+        let vs = r.items.iter().map(|i| i.v).collect::<Vec<_>>();
+        assert_eq!(vs, vec![1, 2, 3]);
+        assert_eq!(
+            xml_serialize(r),
+            r#"<root><item v="1"/><item v="2"/><item v="3"/></root>"#
+        );
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        pub struct Entries {
+            #[xmlserde(name = b"item", ty = "child", alias = b"entry")]
+            pub items: Vec<Item>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct UntaggedRoot {
+            #[xmlserde(ty = "untagged_struct")]
+            pub entries: Entries,
+        }
+
+        let xml = r#"<root><entry v="1"/><item v="2"/></root>"#;
+        let r = xml_deserialize_from_str::<UntaggedRoot>(xml).unwrap();
+        assert_eq!(r.entries.items.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_attr() {
+        use xmlserde::SerdeAttr;
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"a")]
+        pub struct A {
+            #[xmlserde(name = b"n", ty = "attr")]
+            pub n: SerdeAttr<u32>,
+            #[xmlserde(name = b"b", ty = "attr")]
+            pub b: Option<SerdeAttr<bool>>,
+            #[xmlserde(ty = "text")]
+            pub t: SerdeAttr<String>,
+        }
+
+        let xml = r#"<a n="12" b="true">x &amp; y</a>"#;
+        let a = xml_deserialize_from_str::<A>(xml).unwrap();
+        assert_eq!(a.n, SerdeAttr(12));
+        assert_eq!(a.b, Some(SerdeAttr(true)));
+        assert_eq!(a.t.0, "x & y");
+        assert_eq!(xml_serialize(a), xml);
+        assert!(<SerdeAttr<u32> as XmlValue>::deserialize("x").is_err());
+    }
+
+    #[test]
+    fn test_with_module() {
+        // A tiny percent-encoding layered on the XML escaping.
+        mod percent {
+            pub fn serialize(v: &str) -> String {
+                v.replace('%', "%25").replace(' ', "%20")
+            }
+
+            pub fn deserialize(s: &str) -> Result<String, String> {
+                Ok(s.replace("%20", " ").replace("%25", "%"))
+            }
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"a")]
+        pub struct A {
+            #[xmlserde(name = b"url", ty = "attr", with = "percent")]
+            pub url: String,
+            #[xmlserde(name = b"opt", ty = "attr", with = "percent")]
+            pub opt: Option<String>,
+            #[xmlserde(ty = "text", with = "percent")]
+            pub t: String,
+        }
+
+        let xml = r#"<a url="a%20&amp;%20b" opt="&quot;100%25&quot;">x%20&lt;%20y</a>"#;
+        let a = xml_deserialize_from_str::<A>(xml).unwrap();
+        assert_eq!(a.url, "a & b");
+        assert_eq!(a.opt.as_deref(), Some("\"100%\""));
+        assert_eq!(a.t, "x < y");
+        assert_eq!(xml_serialize(a), xml);
+    }
+
+    #[test]
+    fn test_attr_escaping_round_trip() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"a")]
+        pub struct A {
+            #[xmlserde(name = b"v", ty = "attr")]
+            pub v: String,
+            #[xmlserde(name = b"raw", ty = "child")]
+            pub raw: Unparsed,
+        }
+
+        let xml = r#"<a v="&lt;&amp;&gt;&quot;"><raw w="&amp;"/></a>"#;
+        let a = xml_deserialize_from_str::<A>(xml).unwrap();
+        assert_eq!(a.v, "<&>\"");
+        assert_eq!(xml_serialize(a), xml);
+    }
+
+    #[test]
+    fn test_enum_selected_by_attr() {
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub struct Circle {
+            #[xmlserde(name = b"r", ty = "attr")]
+            pub r: u8,
+        }
+
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub struct Square {
+            #[xmlserde(name = b"side", ty = "attr")]
+            pub side: u8,
+        }
+
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(type_attr = b"xsi:type")]
+        pub enum Shape {
+            #[xmlserde(name = b"circle", type_value = b"Circle")]
+            Circle(Circle),
+            #[xmlserde(name = b"square", type_value = b"Square")]
+            Square(Square),
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct Root {
+            #[xmlserde(name = b"shape", ty = "child")]
+            pub shapes: Vec<Shape>,
+            #[xmlserde(ty = "untag")]
+            pub others: Vec<Shape>,
+        }
+
+        let xml = r#"<root>
+            <shape xsi:type="Square" side="2"/>
+            <shape><circle r="1"/></shape>
+            <shape xsi:type="Circle" r="3"></shape>
+            <square xsi:type="Circle" r="4"/>
+            <circle r="5"/>
+        </root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(
+            r.shapes,
+            vec![
+                Shape::Square(Square { side: 2 }),
+                Shape::Circle(Circle { r: 1 }),
+                Shape::Circle(Circle { r: 3 }),
+            ]
+        );
+        // The attribute takes precedence over the element name.
+        assert_eq!(
+            r.others,
+            vec![
+                Shape::Circle(Circle { r: 4 }),
+                Shape::Circle(Circle { r: 5 })
+            ]
+        );
+    }
+
+    #[test]
+    fn error_message_names_field() {
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"person")]
+        struct Person {
+            #[xmlserde(name = b"a", ty = "attr")]
+            _age: u32,
+        }
+        let err = xml_deserialize_from_str::<Person>(r#"<person a="old"/>"#)
+            .err()
+            .unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("failed to parse attribute `_age` (tag `a`) from \"old\""));
+    }
+
+    #[test]
+    fn error_message_names_missing_field() {
+        #[derive(XmlDeserialize)]
+        #[xmlserde(root = b"person")]
+        struct Person {
+            #[xmlserde(name = b"a", ty = "attr")]
+            _age: u32,
+        }
+        let err = xml_deserialize_from_str::<Person>(r#"<person/>"#)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "missing field `_age` (tag `a`) at byte 9");
+    }
+
+    #[test]
+    fn serialize_untagged_fragment() {
+        #[derive(XmlSerialize)]
+        struct Child {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: u8,
+        }
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"parent")]
+        struct Parent {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u8,
+            #[xmlserde(name = b"child", ty = "child")]
+            children: Vec<Child>,
+        }
+        let p = Parent {
+            id: 1,
+            children: vec![Child { v: 1 }, Child { v: 2 }],
+        };
+        let result = xml_serialize_untagged(p);
+        assert_eq!(result, r#"<child v="1"/><child v="2"/>"#);
+    }
+
+    #[test]
+    fn deserialize_returns_error() {
+        #[derive(Debug, XmlDeserialize)]
+        struct Name {
+            #[xmlserde(name = b"first", ty = "attr")]
+            _first: String,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"Person")]
+        struct Person {
+            #[xmlserde(name = b"age", ty = "attr")]
+            _age: u16,
+            #[xmlserde(name = b"name", ty = "child")]
+            _name: Option<Name>,
+        }
+        let r = xml_deserialize_from_str::<Person>(r#"<Person age="notanumber"/>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { value, .. }) if value == "notanumber"));
+
+        let r = xml_deserialize_from_str::<Person>(r#"<Person age="1"><name/></Person>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::MissingField { .. })));
+
+        let r = xml_deserialize_from_str::<Person>(r#"<Person age="1"><name"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::Xml(_))));
+
+        let r = xml_deserialize_from_str::<Person>(r#"<Pet/>"#).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::MissingRoot { .. })));
+    }
+
+    #[test]
+    fn de_error_has_field_and_tag() {
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"item")]
+        struct Item {
+            #[xmlserde(name = b"n", ty = "attr")]
+            _count: u8,
+            #[xmlserde(ty = "text")]
+            _price: f64,
+        }
+        match xml_deserialize_from_str::<Item>(r#"<item n="300">1</item>"#)
+            .map_err(XmlDeErrorAt::into_inner)
+        {
+            Err(XmlDeError::AttrParse {
+                field, tag, value, ..
+            }) => {
+                assert_eq!(field, "_count");
+                assert_eq!(tag, "n");
+                assert_eq!(value, "300");
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+        match xml_deserialize_from_str::<Item>(r#"<item n="3">cheap</item>"#)
+            .map_err(XmlDeErrorAt::into_inner)
+        {
+            Err(XmlDeError::TextParse { field, value, .. }) => {
+                assert_eq!(field, "_price");
+                assert_eq!(value, "cheap");
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+        match xml_deserialize_from_str::<Item>(r#"<item>1</item>"#)
+            .map_err(XmlDeErrorAt::into_inner)
+        {
+            Err(XmlDeError::MissingField { field, tag }) => {
+                assert_eq!(field, "_count");
+                assert_eq!(tag.as_deref(), Some("n"));
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn enum_value_as_text() {
+        xml_serde_enum! {
+            #[derive(Debug, PartialEq)]
+            Status {
+                Active => "active",
+                Closed => "closed",
+            }
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"status")]
+        struct StatusElement {
+            #[xmlserde(ty = "text")]
+            value: Status,
+        }
+
+        let xml = r#"<status>active</status>"#;
+        let s = xml_deserialize_from_str::<StatusElement>(xml).unwrap();
+        assert_eq!(s.value, Status::Active);
+        assert_eq!(xml_serialize(s), xml);
+
+        let r = xml_deserialize_from_str::<StatusElement>(r#"<status>open</status>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::TextParse { .. })));
+    }
+
+    #[test]
+    fn de_error_has_position() {
+        #[derive(Debug, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"n", ty = "attr")]
+            _n: u8,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"list")]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child")]
+            _items: Vec<Item>,
+        }
+        let xml = r#"<list><item n="1"/><item n="x"/></list>"#;
+        let err = xml_deserialize_from_str::<List>(xml).err().unwrap();
+        assert_eq!(err.position(), 32);
+        assert!(err.to_string().ends_with("at byte 32"));
+        assert!(matches!(err.into_inner(), XmlDeError::AttrParse { .. }));
+    }
+
+    #[test]
+    fn serialize_pretty_with_tabs() {
+        #[derive(XmlSerialize)]
+        struct Pet {
+            #[xmlserde(name = b"name", ty = "attr")]
+            name: String,
+            #[xmlserde(ty = "text")]
+            kind: String,
+        }
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"person")]
+        struct Person {
+            #[xmlserde(name = b"pets", ty = "child")]
+            pets: Pets,
+        }
+        #[derive(XmlSerialize)]
+        struct Pets {
+            #[xmlserde(name = b"pet", ty = "child")]
+            pets: Vec<Pet>,
+        }
+        let pets = || Pets {
+            pets: vec![
+                Pet {
+                    name: String::from("Tom"),
+                    kind: String::from("cat"),
+                },
+                Pet {
+                    name: String::from("Spike"),
+                    kind: String::from("dog"),
+                },
+            ],
+        };
+        let result = xml_serialize_pretty(Person { pets: pets() }, b'\t', 1);
+        assert_eq!(
+            result,
+            "<person>\n\t<pets>\n\t\t<pet name=\"Tom\">cat</pet>\n\t\t<pet name=\"Spike\">dog</pet>\n\t</pets>\n</person>"
+        );
+        let result = xml_serialize_pretty(Person { pets: pets() }, b' ', 2);
+        assert!(result.contains("\n    <pet name=\"Tom\">cat</pet>"));
+    }
+
+    #[test]
+    fn root_from_const() {
+        const PERSON_TAG: &[u8] = b"person";
+        mod tags {
+            pub const PET: &[u8] = b"pet";
+        }
+
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = PERSON_TAG)]
+        struct Person {
+            #[xmlserde(name = b"age", ty = "attr")]
+            age: u8,
+        }
+
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = "tags::PET")]
+        struct Pet {
+            #[xmlserde(name = b"name", ty = "attr")]
+            name: String,
+        }
+
+        let p = xml_deserialize_from_str::<Person>(r#"<person age="8"/>"#).unwrap();
+        assert_eq!(p.age, 8);
+        assert_eq!(xml_serialize(p), r#"<person age="8"/>"#);
+
+        let p = xml_deserialize_from_str::<Pet>(r#"<pet name="Tom"/>"#).unwrap();
+        assert_eq!(p.name, "Tom");
+        assert_eq!(xml_serialize(p), r#"<pet name="Tom"/>"#);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_values() {
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"event")]
+        pub struct Event {
+            #[xmlserde(name = b"at", ty = "attr")]
+            pub at: DateTime<Utc>,
+            #[xmlserde(name = b"local", ty = "attr")]
+            pub local: NaiveDateTime,
+            #[xmlserde(name = b"day", ty = "attr")]
+            pub day: NaiveDate,
+        }
+
+        let xml = r#"<event at="2023-01-02T03:04:05Z" local="2023-01-02T03:04:05.250" day="2023-01-02"/>"#;
+        let e = xml_deserialize_from_str::<Event>(xml).unwrap();
+        assert_eq!(e.day, NaiveDate::from_ymd_opt(2023, 1, 2).unwrap());
+        assert_eq!(e.at.naive_utc(), e.local.with_nanosecond(0).unwrap());
+        assert_eq!(xml_serialize(e), xml);
+
+        let e = xml_deserialize_from_str::<Event>(
+            r#"<event at="2023-01-02T05:04:05+02:00" local="2023-01-02T03:04:05" day="2023-01-02"/>"#,
+        )
+        .unwrap();
+        assert_eq!(e.at.to_string(), "2023-01-02 03:04:05 UTC");
+        assert!(<DateTime<Utc> as XmlValue>::deserialize("2023-01-02").is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid_value() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"node")]
+        pub struct Node {
+            #[xmlserde(name = b"id", ty = "attr")]
+            pub id: uuid::Uuid,
+        }
+
+        let xml = r#"<node id="67e55044-10b1-426f-9247-bb680e5fe0c8"/>"#;
+        let n = xml_deserialize_from_str::<Node>(xml).unwrap();
+        assert_eq!(n.id.as_u128(), 0x67e5504410b1426f9247bb680e5fe0c8);
+        assert_eq!(xml_serialize(n), xml);
+
+        let n =
+            xml_deserialize_from_str::<Node>(r#"<node id="67E5504410B1426F9247BB680E5FE0C8"/>"#)
+                .unwrap();
+        assert_eq!(xml_serialize(n), xml);
+        assert!(<uuid::Uuid as XmlValue>::deserialize("67e55044").is_err());
+    }
+
+    #[test]
+    fn serialize_selected_fields() {
+        #[derive(XmlSerialize)]
+        struct Pet {
+            #[xmlserde(name = b"name", ty = "attr")]
+            name: String,
+        }
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"person", selectable)]
+        struct Person {
+            #[xmlserde(name = b"name", ty = "attr")]
+            name: String,
+            #[xmlserde(name = b"age", ty = "attr")]
+            age: u8,
+            #[xmlserde(name = b"pet", ty = "child")]
+            pets: Vec<Pet>,
+        }
+        let p = Person {
+            name: String::from("Tom"),
+            age: 8,
+            pets: vec![Pet {
+                name: String::from("Spike"),
+            }],
+        };
+        let select = |fields: &[&str]| {
+            let mut writer = xmlserde::quick_xml::Writer::new(Vec::new());
+            p.serialize_selected(fields, b"person", &mut writer);
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+        assert_eq!(select(&["name", "age"]), r#"<person name="Tom" age="8"/>"#);
+        assert_eq!(select(&["pets"]), r#"<person><pet name="Spike"/></person>"#);
+        assert_eq!(select(&[]), r#"<person/>"#);
+        assert_eq!(
+            xml_serialize(p),
+            r#"<person name="Tom" age="8"><pet name="Spike"/></person>"#
+        );
+        #[derive(XmlSerialize)]
+        #[xmlserde(selectable)]
+        struct Note {
+            #[xmlserde(name = b"lang", ty = "attr")]
+            lang: String,
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+        let n = Note {
+            lang: String::from("en"),
+            text: String::from("hi"),
+        };
+        let mut writer = xmlserde::quick_xml::Writer::new(Vec::new());
+        n.serialize_selected(&["lang"], b"note", &mut writer);
+        assert_eq!(writer.into_inner(), br#"<note lang="en"/>"#);
+    }
+
+    #[test]
+    fn char_value() {
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"cell")]
+        struct Cell {
+            #[xmlserde(name = b"align", ty = "attr")]
+            align: char,
+        }
+        let xml = r#"<cell align="L"/>"#;
+        let c = xml_deserialize_from_str::<Cell>(xml).unwrap();
+        assert_eq!(c.align, 'L');
+        assert_eq!(xml_serialize(c), xml);
+
+        assert_eq!(char::deserialize("é"), Ok('é'));
+        assert!(char::deserialize("").is_err());
+        assert!(char::deserialize("LR").is_err());
+    }
+
+    #[test]
+    fn str_pointer_values() {
+        use std::sync::Arc;
+
+        #[derive(XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"item")]
+        struct Item {
+            #[xmlserde(name = b"kind", ty = "attr")]
+            kind: Arc<str>,
+            #[xmlserde(ty = "text")]
+            text: Box<str>,
+        }
+        let xml = r#"<item kind="a &amp; b">x &lt; y</item>"#;
+        let i = xml_deserialize_from_str::<Item>(xml).unwrap();
+        assert_eq!(&*i.kind, "a & b");
+        assert_eq!(&*i.text, "x < y");
+        assert_eq!(xml_serialize(i), xml);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn test_base64_encode() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"blob")]
+        pub struct Blob {
+            #[xmlserde(name = b"key", ty = "attr", encode = "base64")]
+            pub key: Option<Vec<u8>>,
+            #[xmlserde(ty = "text", encode = "base64")]
+            pub data: Vec<u8>,
+        }
+
+        let xml = r#"<blob key="AAE=">aGVsbG8gd29ybGQ=</blob>"#;
+        let b = xml_deserialize_from_str::<Blob>(xml).unwrap();
+        assert_eq!(b.key, Some(vec![0, 1]));
+        assert_eq!(b.data, b"hello world");
+        assert_eq!(xml_serialize(b), xml);
+
+        let b = xml_deserialize_from_str::<Blob>("<blob>aGVsbG8g\n  d29ybGQ=</blob>").unwrap();
+        assert_eq!(b.data, b"hello world");
+
+        let r = xml_deserialize_from_str::<Blob>(r#"<blob>not base64!</blob>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::TextParse { .. })));
+    }
+
+    #[test]
+    fn attr_prefix() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"w:b")]
+        #[xmlserde(with_custom_ns(b"w", b"http://w"))]
+        struct Bold {
+            #[xmlserde(name = b"val", ty = "attr", prefix = b"w")]
+            val: u8,
+            #[xmlserde(name = b"id", ty = "attr", prefix = b"w", ns = b"http://w")]
+            id: u8,
+        }
+        let xml = r#"<w:b xmlns:w="http://w" w:val="1" w:id="2"/>"#;
+        let b = xml_deserialize_from_str::<Bold>(xml).unwrap();
+        assert_eq!(b.val, 1);
+        assert_eq!(b.id, 2);
+        assert_eq!(xml_serialize(b), xml);
+
+        let xml = r#"<w:b xmlns:w="http://w" xmlns:x="http://w" w:val="1" x:id="2"/>"#;
+        let b = xml_deserialize_from_str::<Bold>(xml).unwrap();
+        assert_eq!(b.id, 2);
+    }
+
+    #[test]
+    fn strict_eof() {
+        use xmlserde::{xml_deserialize_from_str_with_options, DeOptions};
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"a")]
+        struct A {
+            #[xmlserde(name = b"v", ty = "attr")]
+            _v: u8,
+        }
+        let strict = DeOptions {
+            strict_eof: true,
+            ..Default::default()
+        };
+        let xml = "<a v=\"1\"/>\n<!-- end -->\n";
+        assert!(xml_deserialize_from_str_with_options::<A>(xml, &strict).is_ok());
+
+        let xml = r#"<a v="1"/><a v="2"/>"#;
+        assert!(xml_deserialize_from_str::<A>(xml).is_ok());
+        let r = xml_deserialize_from_str_with_options::<A>(xml, &strict)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::TrailingContent { content }) if content == "a"));
+
+        let r = xml_deserialize_from_str_with_options::<A>(r#"<a v="1"/>junk"#, &strict)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::TrailingContent { .. })));
+    }
+
+    #[test]
+    fn pretty_round_trip() {
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub enum Item {
+            #[xmlserde(name = b"a")]
+            A(Leaf),
+            #[xmlserde(name = b"b")]
+            B,
+        }
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        pub struct Leaf {
+            #[xmlserde(name = b"k", ty = "attr")]
+            k: u8,
+            #[xmlserde(ty = "text")]
+            text: Option<String>,
+        }
+        #[derive(Debug, PartialEq, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        pub struct Root {
+            #[xmlserde(name = b"leaf", ty = "child")]
+            leaves: Vec<Leaf>,
+            #[xmlserde(name = b"flag", ty = "sfc")]
+            flag: bool,
+            #[xmlserde(ty = "untag")]
+            items: Vec<Item>,
+        }
+        let root = || Root {
+            leaves: vec![
+                Leaf {
+                    k: 1,
+                    text: Some(String::from("x y")),
+                },
+                Leaf {
+                    k: 2,
+                    text: Some(String::from("z")),
+                },
+            ],
+            flag: true,
+            items: vec![
+                Item::B,
+                Item::A(Leaf {
+                    k: 3,
+                    text: Some(String::from("w")),
+                }),
+            ],
+        };
+        let xml = xml_serialize_pretty(root(), b' ', 2);
+        assert_eq!(
+            xml,
+            "<root>\n  <flag/>\n  <leaf k=\"1\">x y</leaf>\n  <leaf k=\"2\">z</leaf>\n  <b/>\n  <a k=\"3\">w</a>\n</root>"
+        );
+        let result = xml_deserialize_from_str::<Root>(&xml).unwrap();
+        assert_eq!(result, root());
+    }
+
+    #[test]
+    fn serialize_into_writer() {
+        use xmlserde::xml_serialize_into;
+
+        #[derive(XmlSerialize)]
+        #[xmlserde(root = b"person")]
+        struct Person {
+            #[xmlserde(name = b"name", ty = "child")]
+            names: Vec<Name>,
+        }
+        #[derive(XmlSerialize)]
+        struct Name {
+            #[xmlserde(ty = "text")]
+            v: String,
+        }
+        let person = || Person {
+            names: vec![
+                Name {
+                    v: String::from("Tom"),
+                },
+                Name {
+                    v: String::from("Jerry"),
+                },
+            ],
+        };
+        let mut buf = Vec::new();
+        xml_serialize_into(person(), &mut buf).unwrap();
+        assert_eq!(buf, xml_serialize_to_bytes(person()));
+
+        let mut small = [0u8; 16];
+        let r = xml_serialize_into(person(), &mut small[..]);
+        assert_eq!(r.unwrap_err().kind(), std::io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn deserialize_many_roots() {
+        use xmlserde::xml_deserialize_many;
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"doc")]
+        struct Doc {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u8,
+            #[xmlserde(ty = "text")]
+            text: Option<String>,
+        }
+        let xml = "<doc id=\"1\"/>\n<doc id=\"2\">b</doc>\n<doc id=\"3\"></doc>\n";
+        let docs = xml_deserialize_many::<Doc, _>(xml.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(docs.iter().map(|d| d.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(docs[1].text.as_deref(), Some("b"));
+
+        let xml = r#"<doc id="1"/><doc id="x"/><doc id="3"/>"#;
+        let docs = xml_deserialize_many::<Doc, _>(xml.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].as_ref().unwrap().id, 1);
+        assert!(docs[1].is_err());
+
+        assert_eq!(xml_deserialize_many::<Doc, _>("".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn radix_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"color")]
+        struct Color {
+            #[xmlserde(name = b"rgb", ty = "attr", radix = 16, width = 6, upper)]
+            rgb: u32,
+            #[xmlserde(name = b"mask", ty = "attr", radix = 2)]
+            mask: Option<u8>,
+            #[xmlserde(ty = "text", radix = 8)]
+            mode: u16,
+        }
+        let xml = r#"<color rgb="00FFaa" mask="101">755</color>"#;
+        let c = xml_deserialize_from_str::<Color>(xml).unwrap();
+        assert_eq!(c.rgb, 0xFFAA);
+        assert_eq!(c.mask, Some(5));
+        assert_eq!(c.mode, 0o755);
+        assert_eq!(
+            xml_serialize(c),
+            r#"<color rgb="00FFAA" mask="101">755</color>"#
+        );
+
+        let r = xml_deserialize_from_str::<Color>(r#"<color rgb="GG">1</color>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { .. })));
+    }
+
+    #[test]
+    fn rename_all_fields() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(rename_all = "camelCase")]
+        struct Cell {
+            #[xmlserde(ty = "attr")]
+            style_id: u32,
+            #[xmlserde(name = b"r", ty = "attr")]
+            cell_ref: String,
+            #[xmlserde(ty = "child")]
+            formula_text: Option<Text>,
+            #[xmlserde(ty = "sfc")]
+            is_hidden: bool,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Text {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"item", rename_all = "kebab-case")]
+        struct Item {
+            #[xmlserde(ty = "attr")]
+            item_id: u32,
+            #[xmlserde(ty = "child")]
+            cell_data: Cell,
+        }
+
+        let xml = r#"<item item-id="1"><cell-data styleId="2" r="A1"><isHidden/><formulaText>SUM(B1)</formulaText></cell-data></item>"#;
+        let item = xml_deserialize_from_str::<Item>(xml).unwrap();
+        assert_eq!(item.item_id, 1);
+        assert_eq!(item.cell_data.style_id, 2);
+        assert_eq!(item.cell_data.cell_ref, "A1");
+        assert_eq!(
+            item.cell_data.formula_text.as_ref().unwrap().value,
+            "SUM(B1)"
+        );
+        assert!(item.cell_data.is_hidden);
+        assert_eq!(xml_serialize(item), xml);
+    }
+
+    #[test]
+    fn verbatim_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"c")]
+        struct C {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: Verbatim<u32>,
+            #[xmlserde(name = b"f", ty = "attr")]
+            f: Option<Verbatim<f64>>,
+        }
+        let xml = r#"<c v="0100" f="1.50"/>"#;
+        let c = xml_deserialize_from_str::<C>(xml).unwrap();
+        assert_eq!(*c.v.value(), 100);
+        assert_eq!(c.v.as_str(), "0100");
+        assert_eq!(*c.f.as_ref().unwrap().value(), 1.5);
+        assert_eq!(xml_serialize(c), xml);
+
+        let c = C {
+            v: Verbatim::new(7),
+            f: None,
+        };
+        assert_eq!(xml_serialize(c), r#"<c v="7"/>"#);
+        assert!(xml_deserialize_from_str::<C>(r#"<c v="x"/>"#).is_err());
+    }
+
+    #[test]
+    fn skip_serializing_if_predicate() {
+        fn is_zero(v: &f64) -> bool {
+            *v == 0.0
+        }
+        fn is_blank(v: &str) -> bool {
+            v.trim().is_empty()
+        }
+        #[derive(Debug, Default, XmlSerialize)]
+        struct Child {
+            #[xmlserde(name = b"w", ty = "attr", skip_serializing_if = "is_zero")]
+            w: f64,
+        }
+        #[derive(Debug, Default, XmlSerialize)]
+        #[xmlserde(root = b"p")]
+        struct P {
+            #[xmlserde(name = b"w", ty = "attr", skip_serializing_if = "is_zero")]
+            w: f64,
+            #[xmlserde(name = b"c", ty = "child", skip_serializing_if = "Vec::is_empty")]
+            c: Vec<Child>,
+            #[xmlserde(name = b"b", ty = "sfc", skip_serializing_if = "Clone::clone")]
+            b: bool,
+        }
+        #[derive(Debug, Default, XmlSerialize)]
+        #[xmlserde(root = b"t")]
+        struct T {
+            #[xmlserde(ty = "text", skip_serializing_if = "is_blank")]
+            t: String,
+        }
+        assert_eq!(xml_serialize(P::default()), "<p/>");
+        let p = P {
+            w: 1.5,
+            c: vec![Child { w: 0.0 }],
+            b: true,
+        };
+        assert_eq!(xml_serialize(p), r#"<p w="1.5"><c/></p>"#);
+        assert_eq!(xml_serialize(T { t: " ".to_string() }), "<t/>");
+        assert_eq!(xml_serialize(T { t: "a".to_string() }), "<t>a</t>");
+    }
+
+    #[test]
+    fn option_unparsed_child() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"doc")]
+        struct Doc {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"ext", ty = "child")]
+            ext: Option<Unparsed>,
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Option<Name>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Name {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+
+        let xml = r#"<doc id="1"><ext a="1"><x:b>t</x:b><c/></ext><name>n</name></doc>"#;
+        let doc = xml_deserialize_from_str::<Doc>(xml).unwrap();
+        assert!(doc.ext.is_some());
+        assert_eq!(doc.name.as_ref().unwrap().value, "n");
+        assert_eq!(xml_serialize(doc), xml);
+
+        let xml = r#"<doc id="1"><name>n</name></doc>"#;
+        let doc = xml_deserialize_from_str::<Doc>(xml).unwrap();
+        assert!(doc.ext.is_none());
+        assert_eq!(xml_serialize(doc), xml);
+    }
+
+    #[test]
+    fn serialize_with_and_deserialize_with() {
+        fn flags_to_tokens(v: &u8) -> String {
+            ["bold", "italic", "underline"]
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| v & (1 << i) != 0)
+                .map(|(_, n)| *n)
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+        fn flags_from_tokens(s: &str) -> Result<u8, String> {
+            s.split_whitespace().try_fold(0u8, |acc, t| match t {
+                "bold" => Ok(acc | 1),
+                "italic" => Ok(acc | 2),
+                "underline" => Ok(acc | 4),
+                _ => Err(format!("unknown flag {}", t)),
+            })
+        }
+        fn upper(s: &str) -> Result<String, String> {
+            Ok(s.to_uppercase())
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"font")]
+        struct Font {
+            #[xmlserde(
+                name = b"style",
+                ty = "attr",
+                serialize_with = "flags_to_tokens",
+                deserialize_with = "flags_from_tokens"
+            )]
+            style: u8,
+            #[xmlserde(ty = "text", deserialize_with = "upper")]
+            name: String,
+        }
+        let f =
+            xml_deserialize_from_str::<Font>(r#"<font style="italic bold">arial</font>"#).unwrap();
+        assert_eq!(f.style, 3);
+        assert_eq!(f.name, "ARIAL");
+        assert_eq!(
+            xml_serialize(f),
+            r#"<font style="bold italic">ARIAL</font>"#
+        );
+        let r = xml_deserialize_from_str::<Font>(r#"<font style="strike">a</font>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { .. })));
+    }
+
+    #[test]
+    fn deny_duplicate_fields() {
+        #[derive(Debug, XmlDeserialize)]
+        struct Name {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"person", deny_duplicate_fields)]
+        struct Person {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Name,
+            #[xmlserde(name = b"nick", ty = "child")]
+            nick: Option<Name>,
+            #[xmlserde(name = b"tag", ty = "child")]
+            tags: Vec<Name>,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"person")]
+        struct LoosePerson {
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Name,
+        }
+
+        let xml = r#"<person id="1"><name>a</name><nick>b</nick><tag>x</tag><tag>y</tag></person>"#;
+        let p = xml_deserialize_from_str::<Person>(xml).unwrap();
+        assert_eq!(p.id, 1);
+        assert_eq!(p.name.value, "a");
+        assert_eq!(p.nick.unwrap().value, "b");
+        assert_eq!(p.tags.len(), 2);
+
+        let xml = r#"<person id="1"><name>a</name><nick>b</nick><nick>c</nick></person>"#;
+        let r = xml_deserialize_from_str::<Person>(xml).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::DuplicateField { field }) if field == "nick"));
+
+        let xml = r#"<person id="1"><name>a</name><name>b</name></person>"#;
+        let err = xml_deserialize_from_str::<Person>(xml).unwrap_err();
+        assert!(err.to_string().contains("duplicate field `name`"));
+        let p = xml_deserialize_from_str::<LoosePerson>(xml).unwrap();
+        assert_eq!(p.name.value, "b");
+
+        let xml = r#"<person id="1" id="2"><name>a</name></person>"#;
+        let r = xml_deserialize_from_str::<Person>(xml).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::Xml(_))));
+    }
+
+    #[test]
+    fn alias_single_child_and_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Colour {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"shape")]
+        struct Shape {
+            #[xmlserde(name = b"color", alias = b"colour", ty = "attr")]
+            color: Option<String>,
+            #[xmlserde(name = b"fill", alias = b"filling", ty = "child")]
+            fill: Colour,
+            #[xmlserde(name = b"border", alias = b"edge", ty = "child")]
+            border: Option<Colour>,
+        }
+
+        let xml = r#"<shape colour="red"><filling>blue</filling><edge>green</edge></shape>"#;
+        let s = xml_deserialize_from_str::<Shape>(xml).unwrap();
+        assert_eq!(s.color.as_deref(), Some("red"));
+        assert_eq!(s.fill.value, "blue");
+        assert_eq!(s.border.as_ref().unwrap().value, "green");
+        assert_eq!(
+            xml_serialize(s),
+            r#"<shape color="red"><fill>blue</fill><border>green</border></shape>"#
+        );
+
+        let xml = r#"<shape color="red"><fill>blue</fill></shape>"#;
+        let s = xml_deserialize_from_str::<Shape>(xml).unwrap();
+        assert_eq!(s.color.as_deref(), Some("red"));
+        assert_eq!(s.fill.value, "blue");
+        assert!(s.border.is_none());
+    }
+
+    #[test]
+    fn tag_ns_field() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(ty = "tag_ns")]
+            ns: String,
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: u8,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Child {
+            #[xmlserde(ty = "tag_ns")]
+            ns: Option<String>,
+            #[xmlserde(name = b"item", ty = "child")]
+            items: Vec<Item>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(ty = "tag_ns")]
+            ns: Option<String>,
+            #[xmlserde(name = b"a:item", ty = "child")]
+            a: Item,
+            #[xmlserde(name = b"b:item", ty = "child")]
+            b: Item,
+            #[xmlserde(name = b"item", ty = "child")]
+            items: Vec<Item>,
+            #[xmlserde(name = b"child", ty = "child")]
+            child: Child,
+        }
+        let xml = r#"<root xmlns:a="urn:a" xmlns:b="urn:b"><a:item v="1"/><b:item v="2"/><item v="3"/><item xmlns="urn:d" v="4"/><child xmlns="urn:c"><item v="5"/></child></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(r.ns, None);
+        assert_eq!(r.a.ns, "urn:a");
+        assert_eq!(r.b.ns, "urn:b");
+        assert_eq!(r.b.v, 2);
+        assert_eq!(r.items[0].ns, "");
+        assert_eq!(r.items[1].ns, "urn:d");
+        assert_eq!(r.child.ns.as_deref(), Some("urn:c"));
+        assert_eq!(r.child.items[0].ns, "urn:c");
+        assert_eq!(r.child.items[0].v, 5);
+    }
+
+    #[test]
+    fn deserialize_cdata_text() {
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"node")]
+        struct Node {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(ty = "text")]
+            text: Option<String>,
+        }
+        let n = xml_deserialize_from_str::<Node>(r#"<node id="1"><![CDATA[a < b & c]]></node>"#)
+            .unwrap();
+        assert_eq!(n.id, 1);
+        assert_eq!(n.text.as_deref(), Some("a < b & c"));
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"n")]
+        struct Num {
+            #[xmlserde(ty = "text")]
+            v: u32,
+        }
+        let n = xml_deserialize_from_str::<Num>("<n><![CDATA[42]]></n>").unwrap();
+        assert_eq!(n.v, 42);
+        let r = xml_deserialize_from_str::<Num>("<n><![CDATA[x]]></n>")
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::TextParse { value, .. }) if value == "x"));
+
+        #[derive(Debug, XmlDeserialize)]
+        enum Content {
+            #[xmlserde(name = b"b", ty = "child")]
+            Bold(Node),
+            #[xmlserde(ty = "text")]
+            Text(String),
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"p")]
+        struct P {
+            #[xmlserde(ty = "untag")]
+            contents: Vec<Content>,
+        }
+        let p = xml_deserialize_from_str::<P>(r#"<p>a<b id="2"/><![CDATA[<b>]]></p>"#).unwrap();
+        assert_eq!(p.contents.len(), 3);
+        assert!(matches!(&p.contents[1], Content::Bold(n) if n.id == 2));
+        assert!(matches!(&p.contents[2], Content::Text(t) if t == "<b>"));
+    }
+
+    #[test]
+    fn emit_empty_on_none_child() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Foo {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: Option<u32>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"root")]
+        struct Root {
+            #[xmlserde(name = b"foo", ty = "child", emit_empty_on_none)]
+            foo: Option<Foo>,
+            #[xmlserde(name = b"bar", ty = "child")]
+            bar: Option<Foo>,
+        }
+        let r = Root {
+            foo: None,
+            bar: None,
+        };
+        let xml = xml_serialize(r);
+        assert_eq!(xml, "<root><foo/></root>");
+        let r = xml_deserialize_from_str::<Root>(&xml).unwrap();
+        assert!(r.foo.is_none());
+        assert!(r.bar.is_none());
+
+        let xml = r#"<root><foo v="1"/><bar/></root>"#;
+        let r = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(r.foo.as_ref().unwrap().v, Some(1));
+        assert!(r.bar.as_ref().unwrap().v.is_none());
+        assert_eq!(xml_serialize(r), xml);
+    }
+
+    #[test]
+    fn tag_name_field() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Shape {
+            #[xmlserde(ty = "tag_name")]
+            tag: String,
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"ext", ty = "child")]
+            ext: Option<Unparsed>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"shapes")]
+        struct Shapes {
+            #[xmlserde(ty = "tag_name")]
+            tag: Option<String>,
+            #[xmlserde(name = b"shape", alias = b"sp", alias = b"p:sp", ty = "child")]
+            shapes: Vec<Shape>,
+        }
+        let xml =
+            r#"<shapes><shape id="1"/><sp id="2"><ext a="1"/></sp><p:sp id="3"></p:sp></shapes>"#;
+        let r = xml_deserialize_from_str::<Shapes>(xml).unwrap();
+        assert_eq!(r.tag.as_deref(), Some("shapes"));
+        let tags = r.shapes.iter().map(|s| s.tag.as_str()).collect::<Vec<_>>();
+        assert_eq!(tags, vec!["shape", "sp", "p:sp"]);
+        assert_eq!(r.shapes[2].id, 3);
+        assert!(r.shapes[1].ext.is_some());
+        assert_eq!(
+            xml_serialize(r),
+            r#"<shapes><shape id="1"/><shape id="2"><ext a="1"/></shape><shape id="3"/></shapes>"#
+        );
+    }
+
+    #[test]
+    fn rest_attrs_joined_field() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"cmd", with_custom_ns(b"x", b"urn:x"), deny_unknown_fields)]
+        struct Cmd {
+            #[xmlserde(name = b"name", ty = "attr")]
+            name: String,
+            #[xmlserde(ty = "rest_attrs_joined")]
+            rest: String,
+        }
+        let xml = r#"<cmd xmlns:x="urn:x" name="run" x:b='say "hi"' c="a &amp; b"/>"#;
+        let c = xml_deserialize_from_str::<Cmd>(xml).unwrap();
+        assert_eq!(c.name, "run");
+        assert_eq!(c.rest, r#"x:b="say &quot;hi&quot;" c="a &amp; b""#);
+        assert_eq!(
+            xml_serialize(c),
+            r#"<cmd xmlns:x="urn:x" name="run" x:b="say &quot;hi&quot;" c="a &amp; b"/>"#
+        );
+
+        let c = xml_deserialize_from_str::<Cmd>(r#"<cmd name="a"/>"#).unwrap();
+        assert_eq!(c.rest, "");
+        assert_eq!(xml_serialize(c), r#"<cmd xmlns:x="urn:x" name="a"/>"#);
+    }
+
+    #[test]
+    fn sfc_with_val() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"rPr")]
+        struct RunProperty {
+            #[xmlserde(name = b"b", ty = "sfc", val = b"val")]
+            bold: bool,
+            #[xmlserde(name = b"i", ty = "sfc", val = b"w:val")]
+            italic: bool,
+            #[xmlserde(name = b"u", ty = "sfc")]
+            underline: bool,
+        }
+        let r = RunProperty {
+            bold: false,
+            italic: true,
+            underline: false,
+        };
+        assert_eq!(xml_serialize(r), r#"<rPr><b val="0"/><i w:val="1"/></rPr>"#);
+
+        let r = xml_deserialize_from_str::<RunProperty>(r#"<rPr><b/><i w:val="false"/><u/></rPr>"#)
+            .unwrap();
+        assert!(r.bold);
+        assert!(!r.italic);
+        assert!(r.underline);
+        let r = xml_deserialize_from_str::<RunProperty>(r#"<rPr><b val="0"/></rPr>"#).unwrap();
+        assert!(!r.bold);
+        assert!(!r.italic);
+
+        let r = xml_deserialize_from_str::<RunProperty>(r#"<rPr><b val="x"/></rPr>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { field, .. }) if field == "bold"));
+    }
+
+    #[test]
+    fn flatten_field() {
+        #[derive(Debug, Default, XmlSerialize, XmlDeserialize)]
+        struct Meta {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"created", ty = "attr")]
+            created: Option<String>,
+            #[xmlserde(name = b"note", ty = "child")]
+            notes: Vec<Note>,
+        }
+        #[derive(Debug, Default, XmlSerialize, XmlDeserialize)]
+        struct Note {
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"doc", deny_unknown_fields)]
+        struct Doc {
+            #[xmlserde(name = b"title", ty = "attr")]
+            title: String,
+            #[xmlserde(flatten)]
+            meta: Meta,
+            #[xmlserde(name = b"body", ty = "child")]
+            body: Option<Note>,
+        }
+
+        let xml = r#"<doc title="t" id="1" created="2024"><note>a</note><body>b</body><note>c</note></doc>"#;
+        let doc = xml_deserialize_from_str::<Doc>(xml).unwrap();
+        assert_eq!(doc.title, "t");
+        assert_eq!(doc.meta.id, 1);
+        assert_eq!(doc.meta.created.as_deref(), Some("2024"));
+        let notes = doc
+            .meta
+            .notes
+            .iter()
+            .map(|n| n.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(notes, vec!["a", "c"]);
+        assert_eq!(doc.body.as_ref().unwrap().text, "b");
+        assert_eq!(
+            xml_serialize(doc),
+            r#"<doc title="t" id="1" created="2024"><body>b</body><note>a</note><note>c</note></doc>"#
+        );
+
+        let doc = Doc {
+            title: String::from("a & b"),
+            meta: Meta::default(),
+            body: None,
+        };
+        assert_eq!(xml_serialize(doc), r#"<doc title="a &amp; b" id="0"/>"#);
+
+        let r = xml_deserialize_from_str::<Doc>(r#"<doc title="t" id="1" other="x"/>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::UnknownField { field }) if field == "other"));
+        let r = xml_deserialize_from_str::<Doc>(r#"<doc title="t" id="x"/>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::AttrParse { field, .. }) if field == "id"));
+    }
+
+    #[test]
+    fn attr_map_field() {
+        use std::collections::{BTreeMap, HashMap};
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"el", with_ns = b"urn:el", deny_unknown_fields)]
+        struct El {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(ty = "attr_map")]
+            extra: HashMap<String, String>,
+            #[xmlserde(name = b"child", ty = "child")]
+            child: Option<Child>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Child {
+            #[xmlserde(ty = "attr_map")]
+            extra: BTreeMap<String, String>,
+        }
+        let xml = r#"<el xmlns="urn:el" z="1" id="2" a="x &amp; y" w:b="3"><child k="v"/></el>"#;
+        let el = xml_deserialize_from_str::<El>(xml).unwrap();
+        assert_eq!(el.id, 2);
+        assert_eq!(el.extra.len(), 3);
+        assert_eq!(el.extra["a"], "x & y");
+        assert_eq!(el.extra["w:b"], "3");
+        assert_eq!(el.child.as_ref().unwrap().extra["k"], "v");
+        assert_eq!(
+            xml_serialize(el),
+            r#"<el xmlns="urn:el" id="2" a="x &amp; y" w:b="3" z="1"><child k="v"/></el>"#
+        );
+    }
+
+    #[test]
+    fn map_attr_with_separators() {
+        use std::collections::HashMap;
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"div")]
+        struct Div {
+            #[xmlserde(name = b"style", ty = "attr", map_sep = ";", kv_sep = ":")]
+            style: HashMap<String, String>,
+            #[xmlserde(name = b"data", ty = "attr", map_sep = ",", kv_sep = "=")]
+            data: Option<HashMap<String, String>>,
+        }
+        let xml = r#"<div style="width: 2; color:red;" data="a=1"/>"#;
+        let d = xml_deserialize_from_str::<Div>(xml).unwrap();
+        assert_eq!(d.style.len(), 2);
+        assert_eq!(d.style["width"], "2");
+        assert_eq!(d.style["color"], "red");
+        assert_eq!(d.data.as_ref().unwrap()["a"], "1");
+        assert_eq!(
+            xml_serialize(d),
+            r#"<div style="color:red;width:2" data="a=1"/>"#
+        );
+
+        let r = xml_deserialize_from_str::<Div>(r#"<div style="color:red;bold"/>"#)
+            .map_err(XmlDeErrorAt::into_inner);
+        match r {
+            Err(XmlDeError::AttrParse { field, reason, .. }) => {
+                assert_eq!(field, "style");
+                assert_eq!(reason, "missing `:` in the pair `bold`");
+            }
+            _ => panic!("should fail"),
+        }
+    }
+
+    #[test]
+    fn untag_enum_text_or_child_with_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct C {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: Option<u8>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        enum Content {
+            #[xmlserde(name = b"c")]
+            Child(C),
+            #[xmlserde(ty = "text")]
+            Text(String),
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"p")]
+        struct P {
+            #[xmlserde(name = b"a", ty = "attr")]
+            a: u32,
+            #[xmlserde(ty = "untag")]
+            content: Content,
+        }
+
+        // The attributes are read from the start tag and never compete with the content.
+        let xml = r#"<p a="1">text</p>"#;
+        let p = xml_deserialize_from_str::<P>(xml).unwrap();
+        assert_eq!(p.a, 1);
+        assert!(matches!(&p.content, Content::Text(t) if t == "text"));
+        assert_eq!(xml_serialize(p), xml);
+
+        let xml = r#"<p a="2"><c v="3"/></p>"#;
+        let p = xml_deserialize_from_str::<P>(xml).unwrap();
+        assert_eq!(p.a, 2);
+        assert!(matches!(&p.content, Content::Child(c) if c.v == Some(3)));
+        assert_eq!(xml_serialize(p), xml);
+
+        let r = xml_deserialize_from_str::<P>(r#"<p a="1"/>"#).map_err(XmlDeErrorAt::into_inner);
+        assert!(matches!(r, Err(XmlDeError::MissingField { .. })));
+    }
+
+    #[test]
+    fn any_children() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Name {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"person")]
+        struct Person {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Name,
+            #[xmlserde(ty = "any")]
+            rest: Vec<Unparsed>,
+        }
+        let xml =
+            r#"<person id="1"><x:age v="3"/><name>a</name><hobby><item>b</item></hobby></person>"#;
+        let p = xml_deserialize_from_str::<Person>(xml).unwrap();
+        assert_eq!(p.name.value, "a");
+        let tags = p.rest.iter().map(|u| u.tag()).collect::<Vec<_>>();
+        assert_eq!(tags, vec!["x:age", "hobby"]);
+        assert_eq!(p.rest[1].text(), "b");
+        assert_eq!(
+            xml_serialize(p),
+            r#"<person id="1"><name>a</name><x:age v="3"/><hobby><item>b</item></hobby></person>"#
+        );
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"bag")]
+        struct Bag {
+            #[xmlserde(ty = "any")]
+            rest: Vec<Unparsed>,
+        }
+        let xml = r#"<bag><a/><b>t</b></bag>"#;
+        let b = xml_deserialize_from_str::<Bag>(xml).unwrap();
+        assert_eq!(b.rest.len(), 2);
+        assert_eq!(xml_serialize(b), xml);
+        let b = xml_deserialize_from_str::<Bag>("<bag/>").unwrap();
+        assert_eq!(xml_serialize(b), "<bag/>");
+    }
+
+    #[test]
+    fn capacity_of_attr_map_and_any() {
+        use std::collections::HashMap;
+        fn default_zero() -> u32 {
+            0
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"el")]
+        struct El {
+            #[xmlserde(name = b"cnt", ty = "attr", default = "default_zero")]
+            cnt: u32,
+            #[xmlserde(ty = "attr_map", capacity = 64)]
+            extra: HashMap<String, String>,
+            #[xmlserde(ty = "any", capacity = "cnt")]
+            rest: Vec<Unparsed>,
+        }
+        let xml = r#"<el cnt="20" a="1"><x/><y/></el>"#;
+        let el = xml_deserialize_from_str::<El>(xml).unwrap();
+        assert!(el.extra.capacity() >= 64);
+        assert_eq!(el.extra["a"], "1");
+        assert!(el.rest.capacity() >= 20);
+        assert_eq!(el.rest.len(), 2);
+        assert_eq!(xml_serialize(el), xml);
+    }
+
+    #[test]
+    fn untag_default_on_unmatched() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize, PartialEq)]
+        enum Shape {
+            #[xmlserde(name = b"circle")]
+            Circle(Circle),
+            #[xmlserde(name = b"none")]
+            Nothing,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize, PartialEq)]
+        struct Circle {
+            #[xmlserde(name = b"r", ty = "attr")]
+            r: u32,
+        }
+        fn default_shape() -> Shape {
+            Shape::Nothing
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"drawing")]
+        struct Drawing {
+            #[xmlserde(ty = "untag", default = "default_shape")]
+            shape: Shape,
+        }
+        let d =
+            xml_deserialize_from_str::<Drawing>(r#"<drawing><circle r="2"/></drawing>"#).unwrap();
+        assert_eq!(d.shape, Shape::Circle(Circle { r: 2 }));
+        let d = xml_deserialize_from_str::<Drawing>(r#"<drawing><square/></drawing>"#).unwrap();
+        assert_eq!(d.shape, Shape::Nothing);
+        let d = xml_deserialize_from_str::<Drawing>(r#"<drawing/>"#).unwrap();
+        assert_eq!(d.shape, Shape::Nothing);
+    }
+
+    #[test]
+    fn xml_serde_enum_fallback() {
+        xml_serde_enum! {
+            #[derive(Debug, PartialEq)]
+            Color {
+                Red => "red",
+                Green => "green",
+                _ => Unknown
+            }
+        }
+        assert_eq!(Color::deserialize("green"), Ok(Color::Green));
+        assert_eq!(Color::deserialize("blue"), Ok(Color::Unknown));
+        assert_eq!(Color::Red.serialize(), "red");
+
+        xml_serde_enum! {
+            #[derive(Debug)]
+            Size {
+                Small => "s",
+            }
+        }
+        assert_eq!(
+            Size::deserialize("xl").unwrap_err(),
+            "unknown value xl for Size"
+        );
+    }
+
+    #[test]
+    fn tuple_attr_with_sep() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"shape")]
+        struct Shape {
+            #[xmlserde(name = b"range", ty = "attr", sep = "-")]
+            range: (u32, u32),
+            #[xmlserde(name = b"point", ty = "attr", sep = ",")]
+            point: (f64, f64, String),
+        }
+        let xml = r#"<shape range="1-10" point="3,4.5,z"/>"#;
+        let s = xml_deserialize_from_str::<Shape>(xml).unwrap();
+        assert_eq!(s.range, (1, 10));
+        assert_eq!(s.point, (3.0, 4.5, String::from("z")));
+        assert_eq!(xml_serialize(s), xml);
+
+        let err = xml_deserialize_from_str::<Shape>(r#"<shape range="1-10-20" point="3,4,z"/>"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("expect 2 items but found 3"), "{}", err);
+    }
+
+    #[test]
+    fn opt_child_empty_or_populated() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize, PartialEq)]
+        struct Font {
+            #[xmlserde(name = b"size", ty = "attr", default = "default_size")]
+            size: u32,
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Option<Name>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize, PartialEq)]
+        struct Name {
+            #[xmlserde(name = b"val", ty = "attr")]
+            val: String,
+        }
+        fn default_size() -> u32 {
+            11
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"style")]
+        struct Style {
+            #[xmlserde(name = b"font", ty = "child")]
+            font: Option<Font>,
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Option<Name>,
+        }
+        let s = xml_deserialize_from_str::<Style>(r#"<style><font/></style>"#).unwrap();
+        assert_eq!(
+            s.font,
+            Some(Font {
+                size: 11,
+                name: None
+            })
+        );
+
+        let s = xml_deserialize_from_str::<Style>(
+            r#"<style><font size="9"><name val="Arial"/></font></style>"#,
+        )
+        .unwrap();
+        let font = s.font.unwrap();
+        assert_eq!(font.size, 9);
+        assert_eq!(font.name.unwrap().val, "Arial");
+
+        let s = xml_deserialize_from_str::<Style>(r#"<style/>"#).unwrap();
+        assert!(s.font.is_none());
+
+        for xml in [
+            r#"<style><name/></style>"#,
+            r#"<style><name></name></style>"#,
+        ] {
+            let err = xml_deserialize_from_str::<Style>(xml)
+                .unwrap_err()
+                .into_inner();
+            assert!(matches!(&err, XmlDeError::MissingField { field, .. } if field == "val"));
+        }
+    }
+
+    #[test]
+    fn skip_serializing_if_fn() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"length")]
+        struct Length {
+            #[xmlserde(name = b"value", ty = "attr")]
+            value: u32,
+            #[xmlserde(name = b"unit", ty = "attr", skip_serializing_if_fn = "Self::no_unit")]
+            unit: String,
+            #[xmlserde(name = b"note", ty = "text", skip_serializing_if_fn = "Self::no_unit")]
+            note: String,
+        }
+        impl Length {
+            fn no_unit(&self) -> bool {
+                self.value == 0
+            }
+        }
+        let l = Length {
+            value: 3,
+            unit: String::from("cm"),
+            note: String::from("n"),
+        };
+        assert_eq!(
+            xml_serialize(l),
+            r#"<length value="3" unit="cm">n</length>"#
+        );
+        let l = Length {
+            value: 0,
+            unit: String::from("cm"),
+            note: String::from("n"),
+        };
+        assert_eq!(xml_serialize(l), r#"<length value="0"/>"#);
+    }
+
+    #[test]
+    fn bool_format_true_false() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"option")]
+        struct Opt {
+            #[xmlserde(name = b"enabled", ty = "attr", bool_format = "true_false")]
+            enabled: bool,
+            #[xmlserde(name = b"hidden", ty = "attr", bool_format = "true_false")]
+            hidden: Option<bool>,
+            #[xmlserde(name = b"locked", ty = "attr")]
+            locked: bool,
+            #[xmlserde(ty = "text", bool_format = "true_false")]
+            value: bool,
+        }
+        let o = Opt {
+            enabled: true,
+            hidden: Some(false),
+            locked: true,
+            value: false,
+        };
+        let xml = r#"<option enabled="true" hidden="false" locked="1">false</option>"#;
+        assert_eq!(xml_serialize(o), xml);
+        let o =
+            xml_deserialize_from_str::<Opt>(r#"<option enabled="1" locked="true">TRUE</option>"#)
+                .unwrap();
+        assert!(o.enabled && o.locked && o.value);
+        assert!(o.hidden.is_none());
+    }
+
+    #[test]
+    fn attr_order_round_trip() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"c")]
+        struct Cell {
+            #[xmlserde(name = b"r", ty = "attr")]
+            r: String,
+            #[xmlserde(name = b"s", ty = "attr")]
+            style: Option<u32>,
+            #[xmlserde(name = b"t", ty = "attr")]
+            t: Option<String>,
+            #[xmlserde(ty = "rest_attrs_joined")]
+            rest: String,
+            #[xmlserde(ty = "attr_order")]
+            order: Vec<String>,
+        }
+        let xml = r#"<c t="s" x="&amp;" r="A1" s="2"/>"#;
+        let c = xml_deserialize_from_str::<Cell>(xml).unwrap();
+        assert_eq!(c.order, vec!["t", "x", "r", "s"]);
+        assert_eq!(xml_serialize(c), xml);
+
+        let c = Cell {
+            r: String::from("B2"),
+            style: Some(1),
+            t: None,
+            rest: String::new(),
+            order: vec![String::from("s")],
+        };
+        assert_eq!(xml_serialize(c), r#"<c s="1" r="B2"/>"#);
+    }
+
+    #[test]
+    fn lenient_entities() {
+        use xmlserde::{xml_deserialize_from_str_with_options, DeOptions};
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"title")]
+        struct Title {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        let xml = r#"<title>Tom & Jerry &amp; &unknown; &#65;&</title>"#;
+        assert!(xml_deserialize_from_str::<Title>(xml).is_err());
+        let options = DeOptions {
+            lenient_entities: true,
+            ..Default::default()
+        };
+        let t = xml_deserialize_from_str_with_options::<Title>(xml, &options).unwrap();
+        assert_eq!(t.value, "Tom & Jerry & &unknown; A&");
+        // The option does not leak into the later calls.
+        assert!(xml_deserialize_from_str::<Title>(xml).is_err());
+    }
+
+    #[test]
+    fn options_reset_after_panic_and_in_many() {
+        use xmlserde::{
+            xml_deserialize_from_str_with_options, xml_deserialize_many_with_options, DeOptions,
+            XmlValue,
+        };
+
+        #[derive(Debug)]
+        struct Boom;
+        impl XmlValue for Boom {
+            fn serialize(&self) -> String {
+                String::new()
+            }
+            fn deserialize(_: &str) -> Result<Self, String> {
+                panic!("boom")
+            }
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"b")]
+        struct B {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: Boom,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"title")]
+        struct Title {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+
+        let options = DeOptions {
+            lenient_entities: true,
+            ..Default::default()
+        };
+        let r = std::panic::catch_unwind(|| {
+            xml_deserialize_from_str_with_options::<B>(r#"<b v="1"/>"#, &options)
+        });
+        assert!(r.is_err());
+        assert!(xml_deserialize_from_str::<Title>("<title>Tom & Jerry</title>").is_err());
+
+        let xml = "<title>Tom & Jerry</title>\n<title>a &amp; b</title>";
+        let titles = xml_deserialize_many_with_options::<Title, _>(xml.as_bytes(), &options)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(titles[0].value, "Tom & Jerry");
+        assert_eq!(titles[1].value, "a & b");
+
+        let options = DeOptions {
+            dtd_entities: true,
+            strict_eof: true,
+            ..Default::default()
+        };
+        let xml =
+            r#"<!DOCTYPE title [<!ENTITY n "x">]><title>&n;</title> <title>y</title><other/>"#;
+        let titles = xml_deserialize_many_with_options::<Title, _>(xml.as_bytes(), &options)
+            .collect::<Vec<_>>();
+        assert_eq!(titles.len(), 3);
+        assert_eq!(titles[0].as_ref().unwrap().value, "x");
+        assert_eq!(titles[1].as_ref().unwrap().value, "y");
+        let err = titles.into_iter().nth(2).unwrap().unwrap_err();
+        assert!(
+            matches!(err.into_inner(), XmlDeError::TrailingContent { content } if content == "other")
+        );
+    }
+
+    #[test]
+    fn bare_bool_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"option")]
+        struct HtmlOption {
+            #[xmlserde(name = b"value", ty = "attr")]
+            value: String,
+            #[xmlserde(name = b"selected", ty = "attr", bare_bool)]
+            selected: bool,
+            #[xmlserde(name = b"disabled", ty = "attr", bare_bool)]
+            disabled: bool,
+            #[xmlserde(ty = "text")]
+            label: String,
+        }
+        let o = HtmlOption {
+            value: String::from("a"),
+            selected: true,
+            disabled: false,
+            label: String::from("A"),
+        };
+        assert_eq!(xml_serialize(o), r#"<option value="a" selected>A</option>"#);
+
+        let o = xml_deserialize_from_str::<HtmlOption>(
+            r#"<option value="b" selected="selected" disabled="">B</option>"#,
+        )
+        .unwrap();
+        assert!(o.selected && o.disabled);
+        let o = xml_deserialize_from_str::<HtmlOption>(r#"<option value="c">C</option>"#).unwrap();
+        assert!(!o.selected && !o.disabled);
+        assert_eq!((o.value.as_str(), o.label.as_str()), ("c", "C"));
+    }
+
+    #[test]
+    fn child_from_attr() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"box")]
+        struct Box3 {
+            #[xmlserde(name = b"dimension", ty = "child", from_attr = b"value")]
+            dimensions: Vec<u32>,
+            #[xmlserde(name = b"color", ty = "child", from_attr = b"val")]
+            color: Option<String>,
+            #[xmlserde(name = b"weight", ty = "child", from_attr = b"value")]
+            weight: f64,
+        }
+        let xml = r#"<box><dimension value="5"/><weight value="1.5" unit="kg">ignored</weight><dimension value="6"/></box>"#;
+        let b = xml_deserialize_from_str::<Box3>(xml).unwrap();
+        assert_eq!(b.dimensions, vec![5, 6]);
+        assert_eq!(b.color, None);
+        assert_eq!(b.weight, 1.5);
+        assert_eq!(
+            xml_serialize(b),
+            r#"<box><dimension value="5"/><dimension value="6"/><weight value="1.5"/></box>"#
+        );
+
+        let err = xml_deserialize_from_str::<Box3>(r#"<box><weight/></box>"#)
+            .unwrap_err()
+            .into_inner();
+        assert!(matches!(err, XmlDeError::MissingField { field, .. } if field == "weight"));
+        let err = xml_deserialize_from_str::<Box3>(r#"<box><weight value="x"/></box>"#)
+            .unwrap_err()
+            .into_inner();
+        assert!(matches!(err, XmlDeError::AttrParse { field, .. } if field == "weight"));
+    }
+
+    #[test]
+    fn ignore_prefix() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"list")]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child", ignore_prefix)]
+            items: Vec<Item>,
+            #[xmlserde(name = b"a:item", ty = "child")]
+            prefixed: Vec<Item>,
+        }
+        let xml = r#"<list><a:item id="1"/><item id="2"></item><b:item id="3"></b:item></list>"#;
+        let l = xml_deserialize_from_str::<List>(xml).unwrap();
+        let ids = l.items.iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(l.prefixed.is_empty());
+        assert_eq!(
+            xml_serialize(l),
+            r#"<list><item id="1"/><item id="2"/><item id="3"/></list>"#
+        );
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"font", ignore_prefix)]
+        struct Font {
+            #[xmlserde(name = b"name", ty = "child")]
+            name: Item,
+            #[xmlserde(name = b"b", ty = "sfc")]
+            bold: bool,
+        }
+        let f = xml_deserialize_from_str::<Font>(r#"<font><x:name id="7"/><x:b/></font>"#).unwrap();
+        assert_eq!(f.name.id, 7);
+        assert!(f.bold);
+    }
+
+    #[test]
+    fn inline_container() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(inline)]
+        struct Cell {
+            #[xmlserde(name = b"r", ty = "attr")]
+            r: String,
+            #[xmlserde(name = b"v", ty = "child", from_attr = b"val")]
+            v: Option<u32>,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"row", inline, selectable)]
+        struct Row {
+            #[xmlserde(name = b"c", ty = "child")]
+            cells: Vec<Cell>,
+        }
+        let xml = r#"<row><c r="A1"><v val="1"/></c><c r="B1"/></row>"#;
+        let row = xml_deserialize_from_str::<Row>(xml).unwrap();
+        assert_eq!(row.cells[0].v, Some(1));
+        assert_eq!(row.cells[1].r, "B1");
+        assert_eq!(xml_serialize(row), xml);
+    }
+
+    #[test]
+    fn child_ns_and_prefix() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"list", with_custom_ns(b"a", b"urn:a"))]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child", ns = b"urn:a")]
+            items: Vec<Item>,
+            #[xmlserde(name = b"extra", ty = "child", prefix = b"a")]
+            extra: Option<Item>,
+            #[xmlserde(name = b"flag", ty = "sfc", ns = b"urn:a")]
+            flag: bool,
+        }
+        let l = List {
+            items: vec![Item { id: 1 }],
+            extra: Some(Item { id: 2 }),
+            flag: true,
+        };
+        let xml = xml_serialize(l);
+        assert_eq!(
+            xml,
+            r#"<list xmlns:a="urn:a"><a:flag/><a:item id="1"/><a:extra id="2"/></list>"#
+        );
+        let l = xml_deserialize_from_str::<List>(&xml).unwrap();
+        assert_eq!((l.items.len(), l.extra.unwrap().id, l.flag), (1, 2, true));
+
+        // Another prefix or the default namespace binds the same namespace.
+        let xml =
+            r#"<list xmlns:x="urn:a" xmlns="urn:a"><x:item id="3"/><item id="4"/><x:flag/></list>"#;
+        let l = xml_deserialize_from_str::<List>(xml).unwrap();
+        let ids = l.items.iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![3, 4]);
+        assert!(l.flag && l.extra.is_none());
+    }
+
+    #[test]
+    fn child_ns_declared_on_itself() {
+        #[derive(Debug, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"list")]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child", ns = b"urn:x")]
+            items: Vec<Item>,
+            #[xmlserde(name = b"flag", ty = "sfc", ns = b"urn:x")]
+            flag: bool,
+        }
+        let xml = r#"<list xmlns:a="urn:y"><a:item xmlns:a="urn:x" id="1"/><item xmlns="urn:x" id="2"/><a:item id="3"/><flag xmlns="urn:x"/></list>"#;
+        let l = xml_deserialize_from_str::<List>(xml).unwrap();
+        let ids = l.items.iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(l.flag);
+    }
+
+    #[test]
+    fn ns_with_alias_and_case_insensitive_names() {
+        #[derive(Debug, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr", ns = b"urn:x", alias = b"key")]
+            id: u32,
+        }
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"list", case_insensitive_names)]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child", ns = b"urn:x", alias = b"entry")]
+            items: Vec<Item>,
+        }
+        let xml = r#"<list xmlns:a="urn:x"><a:item a:id="1"/><a:Entry a:key="2"/><entry a:id="3"/></list>"#;
+        let l = xml_deserialize_from_str::<List>(xml).unwrap();
+        let ids = l.items.iter().map(|i| i.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn capture_document() {
+        use xmlserde::xml_capture_document;
+
+        let xml = r#"<?xml version="1.0"?><a x="1&amp;2"><a><b>t</b></a><c/></a>"#;
+        let (root, doc) = xml_capture_document(xml.as_bytes()).unwrap();
+        assert_eq!(root, b"a");
+        assert_eq!(doc.tag(), "a");
+        assert_eq!(doc.attrs(), &[(String::from("x"), String::from("1&2"))]);
+        assert_eq!(doc.text(), "t");
+        let mut writer = xmlserde::quick_xml::Writer::new(Vec::new());
+        doc.serialize(&root, &mut writer);
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            r#"<a x="1&amp;2"><a><b>t</b></a><c/></a>"#
+        );
+
+        let err = xml_capture_document("<!-- empty -->".as_bytes()).unwrap_err();
+        assert!(matches!(err.into_inner(), XmlDeError::MissingRoot { .. }));
+    }
+
+    #[test]
+    fn expand_empty() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(expand_empty)]
+        struct Name {
+            #[xmlserde(ty = "text")]
+            value: String,
+        }
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"Person", expand_empty)]
+        struct Person {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: Option<u32>,
+            #[xmlserde(name = b"Name", ty = "child")]
+            name: Option<Name>,
+        }
+        let p = Person {
+            id: None,
+            name: None,
+        };
+        assert_eq!(xml_serialize(p), "<Person></Person>");
+        let p = Person {
+            id: Some(1),
+            name: Some(Name {
+                value: String::new(),
+            }),
+        };
+        assert_eq!(xml_serialize(p), r#"<Person id="1"><Name></Name></Person>"#);
+        let p = xml_deserialize_from_str::<Person>(r#"<Person id="2"></Person>"#).unwrap();
+        assert_eq!(p.id, Some(2));
+        assert!(p.name.is_none());
+    }
+
+    #[test]
+    fn xml_serde_enum_integer_values() {
+        xml_serde_enum! {
+            #[derive(Debug, PartialEq)]
+            Priority {
+                Low => 1,
+                High => 3,
+            }
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"task")]
+        struct Task {
+            #[xmlserde(name = b"priority", ty = "attr")]
+            priority: Priority,
+        }
+
+        let task = xml_deserialize_from_str::<Task>(r#"<task priority="3"/>"#).unwrap();
+        assert_eq!(task.priority, Priority::High);
+        assert_eq!(
+            xml_serialize(Task {
+                priority: Priority::Low
+            }),
+            r#"<task priority="1"/>"#
+        );
+        assert_eq!(
+            Priority::deserialize("2").unwrap_err(),
+            "unknown value 2 for Priority"
+        );
+    }
+
+    #[test]
+    fn xml_serde_enum_case_insensitive() {
+        xml_serde_enum! {
+            #[xmlserde(case_insensitive)]
+            #[derive(Debug, PartialEq)]
+            Flag {
+                Yes => "true",
+                No => "false",
+            }
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"opt")]
+        struct Opt {
+            #[xmlserde(name = b"on", ty = "attr")]
+            on: Flag,
+        }
+
+        let opt = xml_deserialize_from_str::<Opt>(r#"<opt on="TRUE"/>"#).unwrap();
+        assert_eq!(opt.on, Flag::Yes);
+        assert_eq!(xml_serialize(opt), r#"<opt on="true"/>"#);
+        assert_eq!(Flag::deserialize("False"), Ok(Flag::No));
+        assert_eq!(
+            Flag::deserialize("yes").unwrap_err(),
+            "unknown value yes for Flag"
+        );
+    }
+
+    #[test]
+    fn ns_attr_with_different_prefixes() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"use")]
+        #[xmlserde(with_custom_ns(b"xlink", b"http://www.w3.org/1999/xlink"))]
+        struct Use {
+            #[xmlserde(
+                name = b"xlink:href",
+                ty = "attr",
+                ns = b"http://www.w3.org/1999/xlink"
+            )]
+            href: Option<String>,
+        }
+
+        let a = xml_deserialize_from_str::<Use>(
+            r##"<use xmlns:xlink="http://www.w3.org/1999/xlink" xlink:href="#a"/>"##,
+        )
+        .unwrap();
+        assert_eq!(a.href.as_deref(), Some("#a"));
+        let b = xml_deserialize_from_str::<Use>(
+            r##"<use xmlns:xl="http://www.w3.org/1999/xlink" xl:href="#b"/>"##,
+        )
+        .unwrap();
+        assert_eq!(b.href.as_deref(), Some("#b"));
+        let c = xml_deserialize_from_str::<Use>(r##"<use xmlns:xl="urn:other" xl:href="#c"/>"##)
+            .unwrap();
+        assert_eq!(c.href, None);
+        assert_eq!(
+            xml_serialize(b),
+            r##"<use xmlns:xlink="http://www.w3.org/1999/xlink" xlink:href="#b"/>"##
+        );
+    }
+
+    #[test]
+    fn opt_vec_child() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"list", deny_duplicate_fields)]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child")]
+            items: Option<Vec<Item>>,
+        }
+
+        let list = xml_deserialize_from_str::<List>(r#"<list/>"#).unwrap();
+        assert!(list.items.is_none());
+        let list = xml_deserialize_from_str::<List>(r#"<list><item id="1"/><item id="2"/></list>"#)
+            .unwrap();
+        let items = list.items.as_ref().unwrap();
+        assert_eq!(items.iter().map(|i| i.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(
+            xml_serialize(list),
+            r#"<list><item id="1"/><item id="2"/></list>"#
+        );
+        assert_eq!(xml_serialize(List { items: None }), r#"<list/>"#);
+    }
+
+    #[test]
+    fn sort_by_child() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(name = b"id", ty = "attr")]
+            id: u32,
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: String,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"list")]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child", sort_by = "id")]
+            items: Vec<Item>,
+        }
+
+        let xml = r#"<list><item id="3" v="a"/><item id="1" v="b"/><item id="3" v="c"/></list>"#;
+        let list = xml_deserialize_from_str::<List>(xml).unwrap();
+        assert_eq!(list.items[0].id, 3);
+        assert_eq!(
+            xml_serialize(list),
+            r#"<list><item id="1" v="b"/><item id="3" v="a"/><item id="3" v="c"/></list>"#
+        );
+    }
+
+    #[test]
+    fn box_recursive_fields() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"node")]
+        struct Node {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: u32,
+            #[xmlserde(name = b"node", ty = "child")]
+            next: Option<Box<Node>>,
+        }
+
+        let xml = r#"<node v="1"><node v="2"><node v="3"/></node></node>"#;
+        let node = xml_deserialize_from_str::<Node>(xml).unwrap();
+        assert_eq!(node.next.as_ref().unwrap().next.as_ref().unwrap().v, 3);
+        assert_eq!(xml_serialize(node), xml);
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        enum Expr {
+            #[xmlserde(name = b"num")]
+            Num(Num),
+            #[xmlserde(name = b"neg")]
+            Neg(Neg),
+            #[xmlserde(name = b"add")]
+            Add(Add),
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Num {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: i32,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Neg {
+            #[xmlserde(ty = "untag")]
+            inner: Box<Expr>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Add {
+            #[xmlserde(ty = "untag")]
+            terms: Vec<Expr>,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"expr")]
+        struct Root {
+            #[xmlserde(ty = "untag")]
+            expr: Box<Expr>,
+        }
+
+        fn eval(e: &Expr) -> i32 {
+            match e {
+                Expr::Num(n) => n.v,
+                Expr::Neg(n) => -eval(&n.inner),
+                Expr::Add(a) => a.terms.iter().map(eval).sum(),
+            }
+        }
+
+        let xml =
+            r#"<expr><add><num v="1"/><neg><add><num v="2"/><num v="3"/></add></neg></add></expr>"#;
+        let root = xml_deserialize_from_str::<Root>(xml).unwrap();
+        assert_eq!(eval(&root.expr), -4);
+        assert_eq!(xml_serialize(root), xml);
+    }
+
+    #[test]
+    fn dtd_entities() {
+        use xmlserde::{xml_deserialize_from_str_with_options, DeOptions};
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"book")]
+        struct Book {
+            #[xmlserde(name = b"publisher", ty = "attr")]
+            publisher: String,
+            #[xmlserde(name = b"title", ty = "child")]
+            title: Title,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        struct Title {
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+
+        let options = DeOptions {
+            dtd_entities: true,
+            ..Default::default()
+        };
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE book [
+  <!-- <!ENTITY ignored "x"> -->
+  <!ENTITY pub "Acme &amp; Sons">
+  <!ENTITY full '&pub; Publishing'>
+  <!ENTITY ext SYSTEM "ext.xml">
+]>
+<book publisher="&full;"><title>The &pub; Story &#33;</title></book>"#;
+        let book = xml_deserialize_from_str_with_options::<Book>(xml, &options).unwrap();
+        assert_eq!(book.publisher, "Acme & Sons Publishing");
+        assert_eq!(book.title.text, "The Acme & Sons Story !");
+
+        assert!(xml_deserialize_from_str::<Book>(xml).is_err());
+
+        let xml = r#"<!DOCTYPE book [<!ENTITY a "&b;"><!ENTITY b "&a;">]><book publisher="&a;"><title/></book>"#;
+        let err = xml_deserialize_from_str_with_options::<Book>(xml, &options).unwrap_err();
+        assert!(matches!(err.into_inner(), XmlDeError::Entity { .. }));
+
+        let mut dtd = String::from(r#"<!ENTITY l0 "lol">"#);
+        for i in 1..10 {
+            let refs = format!("&l{};", i - 1).repeat(10);
+            dtd.push_str(&format!(r#"<!ENTITY l{} "{}">"#, i, refs));
+        }
+        let xml = format!(
+            r#"<!DOCTYPE book [{}]><book publisher="&l9;"><title/></book>"#,
+            dtd
+        );
+        let err = xml_deserialize_from_str_with_options::<Book>(&xml, &options).unwrap_err();
+        assert!(matches!(err.into_inner(), XmlDeError::Entity { .. }));
+    }
+
+    #[test]
+    fn trim_text() {
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"p")]
+        struct P {
+            #[xmlserde(name = b"span", ty = "child")]
+            spans: Vec<Span>,
+            #[xmlserde(ty = "untag", trim)]
+            content: Vec<Content>,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        struct Span {
+            #[xmlserde(ty = "text", trim)]
+            t: String,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        struct Count {
+            #[xmlserde(ty = "text", trim)]
+            n: u32,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        enum Content {
+            #[xmlserde(ty = "text")]
+            Text(String),
+        }
+
+        let xml =
+            "<p>\n    <span> text1 </span>\n    <span><![CDATA[\ttext2\n]]></span>\n    tail\n</p>";
+        let p = xml_deserialize_from_str::<P>(xml).unwrap();
+        assert_eq!(p.spans[0].t, "text1");
+        assert_eq!(p.spans[1].t, "text2");
+        assert!(matches!(p.content.as_slice(), [Content::Text(t)] if t == "tail"));
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"c")]
+        struct Root {
+            #[xmlserde(name = b"count", ty = "child")]
+            count: Count,
+        }
+        let root = xml_deserialize_from_str::<Root>("<c><count>\n  42\n</count></c>").unwrap();
+        assert_eq!(root.count.n, 42);
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"doc")]
+        struct Doc {
+            #[xmlserde(name = b"p", ty = "child")]
+            p: P,
+            #[xmlserde(name = b"span", ty = "child")]
+            spans: Vec<Span>,
+        }
+        let xml = r#"<doc><p xml:space="preserve"><span> a </span><span xml:space="default"> b </span> tail </p><span> c </span></doc>"#;
+        let doc = xml_deserialize_from_str::<Doc>(xml).unwrap();
+        let spans = doc.p.spans.iter().map(|s| s.t.as_str()).collect::<Vec<_>>();
+        assert_eq!(spans, vec![" a ", "b"]);
+        assert!(matches!(doc.p.content.as_slice(), [Content::Text(t)] if t == " tail "));
+        assert_eq!(doc.spans[0].t, "c");
+    }
+
+    #[test]
+    fn text_split_into_events() {
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"p")]
+        struct P {
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+
+        let p = xml_deserialize_from_str::<P>(
+            "<p>a &amp; b<![CDATA[ <c> ]]>d<!-- comment -->e<?pi?>f</p>",
+        )
+        .unwrap();
+        assert_eq!(p.text, "a & b <c> def");
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"n")]
+        struct N {
+            #[xmlserde(ty = "text")]
+            n: u32,
+        }
+
+        let n = xml_deserialize_from_str::<N>("<n>1<!-- thousand -->000</n>").unwrap();
+        assert_eq!(n.n, 1000);
+    }
+
+    #[test]
+    fn name_de_and_name_ser() {
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"config")]
+        struct Config {
+            #[xmlserde(name_de = b"oldName", name_ser = b"newName", ty = "attr")]
+            name: String,
+            #[xmlserde(name = b"host", name_ser = b"server", ty = "child")]
+            host: Host,
+            #[xmlserde(name_de = b"legacy", ty = "sfc")]
+            legacy: bool,
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        struct Host {
+            #[xmlserde(ty = "text")]
+            addr: String,
+        }
+
+        let xml =
+            r#"<config oldName="a" newName="b"><host>h</host><server>s</server><legacy/></config>"#;
+        let config = xml_deserialize_from_str::<Config>(xml).unwrap();
+        assert_eq!(config.name, "a");
+        assert_eq!(config.host.addr, "h");
+        assert!(config.legacy);
+        assert_eq!(
+            xml_serialize(config),
+            r#"<config newName="a"><legacy/><server>h</server></config>"#
+        );
+    }
+
+    #[test]
+    fn net_addr_values() {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"server")]
+        struct Server {
+            #[xmlserde(name = b"addr", ty = "attr")]
+            addr: SocketAddr,
+            #[xmlserde(name = b"ip", ty = "attr")]
+            ip: Option<IpAddr>,
+            #[xmlserde(name = b"v4", ty = "attr")]
+            v4: Option<Ipv4Addr>,
+            #[xmlserde(name = b"v6", ty = "attr")]
+            v6: Option<Ipv6Addr>,
+        }
+
+        let xml = r#"<server addr="127.0.0.1:8080" ip="::1" v4="10.0.0.1" v6="fe80::1"/>"#;
+        let server = xml_deserialize_from_str::<Server>(xml).unwrap();
+        assert_eq!(server.addr, "127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+        assert_eq!(server.ip, Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert_eq!(server.v4, Some(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(xml_serialize(server), xml);
+
+        let server = xml_deserialize_from_str::<Server>(r#"<server addr="[::1]:80"/>"#).unwrap();
+        assert_eq!(xml_serialize(server), r#"<server addr="[::1]:80"/>"#);
+
+        let err = xml_deserialize_from_str::<Server>(r#"<server addr="localhost"/>"#).unwrap_err();
+        assert!(matches!(err.into_inner(), XmlDeError::AttrParse { .. }));
+    }
+
+    #[test]
+    fn bool_text_with_default() {
+        fn false_fn() -> bool {
+            false
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"flag")]
+        struct Flag {
+            #[xmlserde(ty = "text", default = "false_fn")]
+            on: bool,
+        }
+
+        assert_eq!(xml_serialize(Flag { on: false }), "<flag/>");
+        assert_eq!(xml_serialize(Flag { on: true }), "<flag>1</flag>");
+        assert!(!xml_deserialize_from_str::<Flag>("<flag/>").unwrap().on);
+        assert!(
+            xml_deserialize_from_str::<Flag>("<flag>1</flag>")
+                .unwrap()
+                .on
+        );
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"opts")]
+        struct Opts {
+            #[xmlserde(name = b"flag", ty = "child")]
+            flag: Flag,
+        }
+
+        let opts = Opts {
+            flag: Flag { on: false },
+        };
+        assert_eq!(xml_serialize(opts), "<opts><flag/></opts>");
+        let opts = xml_deserialize_from_str::<Opts>("<opts><flag/></opts>").unwrap();
+        assert!(!opts.flag.on);
+        let opts = Opts {
+            flag: Flag { on: true },
+        };
+        assert_eq!(xml_serialize(opts), "<opts><flag>1</flag></opts>");
+    }
+
+    #[test]
+    fn buffer_capacity_option() {
+        use xmlserde::{xml_deserialize_from_str_with_options, DeOptions};
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"list")]
+        struct List {
+            #[xmlserde(name = b"item", ty = "child")]
+            items: Vec<Item>,
+        }
+
+        #[derive(Debug, XmlDeserialize)]
+        struct Item {
+            #[xmlserde(ty = "text")]
+            text: String,
+        }
+
+        let long = "x".repeat(100_000);
+        let xml = format!("<list><item>{}</item><item>b</item></list>", long);
+        let options = DeOptions {
+            buffer_capacity: 1 << 17,
+            ..Default::default()
+        };
+        let list = xml_deserialize_from_str_with_options::<List>(&xml, &options).unwrap();
+        assert_eq!(list.items[0].text, long);
+        assert_eq!(list.items[1].text, "b");
+        let list = xml_deserialize_from_str::<List>(&xml).unwrap();
+        assert_eq!(list.items.len(), 2);
+    }
+
+    #[test]
+    fn duration_values() {
+        use std::time::Duration;
+        use xmlserde::{DurationMillis, DurationSecs};
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"job")]
+        struct Job {
+            #[xmlserde(name = b"timeout", ty = "attr")]
+            timeout: DurationSecs,
+            #[xmlserde(name = b"delay", ty = "attr")]
+            delay: Option<DurationMillis>,
+        }
+
+        let xml = r#"<job timeout="30" delay="1500"/>"#;
+        let job = xml_deserialize_from_str::<Job>(xml).unwrap();
+        assert_eq!(job.timeout.0, Duration::from_secs(30));
+        assert_eq!(job.delay, Some(DurationMillis(Duration::from_millis(1500))));
+        assert_eq!(xml_serialize(job), xml);
+
+        let job = Job {
+            timeout: Duration::from_millis(2999).into(),
+            delay: None,
+        };
+        assert_eq!(xml_serialize(job), r#"<job timeout="2"/>"#);
+
+        assert!(xml_deserialize_from_str::<Job>(r#"<job timeout="-1"/>"#).is_err());
+    }
+
+    #[test]
+    fn xml_serde_enum_in_attr_list() {
+        xml_serde_enum! {
+            #[derive(Debug, PartialEq)]
+            Role {
+                Admin => "admin",
+                Editor => "editor",
+                Viewer => "viewer",
+            }
+        }
+
+        #[derive(Debug, XmlSerialize, XmlDeserialize)]
+        #[xmlserde(root = b"user")]
+        struct User {
+            #[xmlserde(name = b"roles", ty = "attr", sep = " ")]
+            roles: Vec<Role>,
+        }
+
+        let user = xml_deserialize_from_str::<User>(r#"<user roles="admin  viewer"/>"#).unwrap();
+        assert_eq!(user.roles, vec![Role::Admin, Role::Viewer]);
+        assert_eq!(xml_serialize(user), r#"<user roles="admin viewer"/>"#);
+
+        let err = xml_deserialize_from_str::<User>(r#"<user roles="admin owner"/>"#)
+            .unwrap_err()
+            .into_inner();
+        match err {
+            XmlDeError::AttrParse { field, reason, .. } => {
+                assert_eq!(field, "roles");
+                assert_eq!(reason, "unknown value owner for Role");
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn dtd_entities_limits() {
+        use xmlserde::{xml_deserialize_from_str_with_options, DeOptions};
+
+        #[derive(Debug, XmlDeserialize)]
+        #[xmlserde(root = b"doc")]
+        struct Doc {
+            #[xmlserde(name = b"a", ty = "attr")]
+            a: Option<String>,
+            #[xmlserde(ty = "text")]
+            text: Option<String>,
+        }
+
+        let options = DeOptions {
+            dtd_entities: true,
+            ..Default::default()
+        };
+        let is_entity_error = |xml: &str| {
+            let err = xml_deserialize_from_str_with_options::<Doc>(xml, &options).unwrap_err();
+            matches!(err.into_inner(), XmlDeError::Entity { .. })
+        };
+
+        // Every reference is within the limit of an entity but the document is not.
+        let big = "x".repeat(60 * 1024);
+        let dtd = format!(r#"<!DOCTYPE doc [<!ENTITY big "{}">]>"#, big);
+        let refs = "&big;".repeat(100);
+        let xml = format!("{}<doc>{}</doc>", dtd, refs);
+        assert!(is_entity_error(&xml));
+        let xml = format!(r#"{}<doc a="{}"/>"#, dtd, refs);
+        assert!(is_entity_error(&xml));
+        let xml = format!("{}<doc>&big;&big;</doc>", dtd);
+        let doc = xml_deserialize_from_str_with_options::<Doc>(&xml, &options).unwrap();
+        assert_eq!(doc.text.unwrap().len(), 120 * 1024);
+        let xml = format!(r#"{}<doc a="&big;"/>"#, dtd);
+        let doc = xml_deserialize_from_str_with_options::<Doc>(&xml, &options).unwrap();
+        assert_eq!(doc.a.unwrap(), big);
+
+        // A long chain of references.
+        let chain = |n: usize, reversed: bool| {
+            let mut decls = (1..n)
+                .map(|i| format!(r#"<!ENTITY a{} "&a{};">"#, i, i - 1))
+                .collect::<Vec<_>>();
+            decls.insert(0, String::from(r#"<!ENTITY a0 "end">"#));
+            if reversed {
+                decls.reverse();
+            }
+            format!("<!DOCTYPE doc [{}]><doc>&a{};</doc>", decls.concat(), n - 1)
+        };
+        assert!(is_entity_error(&chain(10_000, false)));
+        assert!(is_entity_error(&chain(10_000, true)));
+        let doc = xml_deserialize_from_str_with_options::<Doc>(&chain(10, true), &options).unwrap();
+        assert_eq!(doc.text.as_deref(), Some("end"));
+    }
+
+    #[test]
+    fn numbers_serialize_into() {
+        let write = |v: &dyn Fn(&mut String)| {
+            let mut buf = String::new();
+            v(&mut buf);
+            buf
+        };
+        assert_eq!(
+            write(&|b| u128::MAX.serialize_into(b)),
+            u128::MAX.to_string()
+        );
+        assert_eq!(
+            write(&|b| i128::MIN.serialize_into(b)),
+            i128::MIN.to_string()
+        );
+        assert_eq!(write(&|b| i8::MIN.serialize_into(b)), "-128");
+        assert_eq!(
+            write(&|b| std::num::NonZeroU32::new(7).unwrap().serialize_into(b)),
+            "7"
+        );
+        assert_eq!(write(&|b| 1.0f64.serialize_into(b)), "1");
+        assert_eq!(write(&|b| (-0.5f64).serialize_into(b)), "-0.5");
+        assert_eq!(write(&|b| 0.1f32.serialize_into(b)), "0.1");
+        assert_eq!(write(&|b| f64::NAN.serialize_into(b)), "NaN");
+        assert_eq!(write(&|b| f64::INFINITY.serialize_into(b)), "inf");
+        // The same strings with or without `fast-num`, and from both methods.
+        for (v, s) in [
+            (1e20f64, "100000000000000000000"),
+            (1e-7f64, "0.0000001"),
+            (2.5f64, "2.5"),
+        ] {
+            assert_eq!(write(&|b| v.serialize_into(b)), s);
+            assert_eq!(v.serialize(), s);
+            assert_eq!(f64::deserialize(s), Ok(v));
+        }
+        assert_eq!(write(&|b| 1e-7f32.serialize_into(b)), 1e-7f32.to_string());
+        assert_eq!(1e-7f32.serialize(), 1e-7f32.to_string());
+
+        #[derive(Debug, XmlSerialize)]
+        #[xmlserde(root = b"r")]
+        struct R {
+            #[xmlserde(name = b"v", ty = "attr")]
+            v: f64,
+            #[xmlserde(ty = "text")]
+            t: f64,
+        }
+        assert_eq!(
+            xml_serialize(R { v: 1e20, t: 1e-7 }),
+            r#"<r v="100000000000000000000">0.0000001</r>"#
+        );
+    }
 }